@@ -0,0 +1,26 @@
+use rust_kata_002::dump::importer;
+use std::env;
+use std::process;
+
+/// Usage: import-db-dump <db-dump.tar.gz> <database>
+///
+/// Loads the official crates.io database dump into the database read by the `dump`
+/// registry backend.
+fn main() {
+    dotenv::dotenv().ok();
+    env_logger::init();
+
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} <db-dump.tar.gz> <database>", args[0]);
+        process::exit(2);
+    }
+
+    match importer::import_file(&args[1], &args[2]) {
+        Ok(summary) => println!("{:?}", summary),
+        Err(err) => {
+            eprintln!("failed to import {}: {}", args[1], err);
+            process::exit(1);
+        }
+    }
+}
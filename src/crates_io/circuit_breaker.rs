@@ -0,0 +1,274 @@
+use crate::observability::metrics;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    static ref CIRCUIT_BREAKERS: Mutex<HashMap<String, Weak<CircuitBreaker>>> =
+        Mutex::new(HashMap::new());
+}
+
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerOptions {
+    /// Consecutive failed calls before the circuit opens.
+    pub failure_threshold: u32,
+    /// How long an open circuit fails fast before a single trial call is let through.
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerOptions {
+    fn default() -> Self {
+        CircuitBreakerOptions {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// Fails calls to an upstream fast once it has failed `failure_threshold` times in a row.
+pub struct CircuitBreaker {
+    base_url: String,
+    options: CircuitBreakerOptions,
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+impl CircuitBreaker {
+    /// Creates a breaker for `base_url` that `circuit_states` does not know about.
+    pub fn new(base_url: &str, options: CircuitBreakerOptions) -> Self {
+        CircuitBreaker {
+            base_url: base_url.to_owned(),
+            options,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+            }),
+        }
+    }
+
+    /// Creates a breaker for `base_url` and makes its state visible to `circuit_states`.
+    /// Clients of the same upstream are meant to share one, see `Upstreams`.
+    pub fn register(base_url: &str, options: CircuitBreakerOptions) -> Arc<Self> {
+        let circuit_breaker = Arc::new(CircuitBreaker::new(base_url, options));
+
+        CIRCUIT_BREAKERS
+            .lock()
+            .unwrap()
+            .insert(base_url.to_owned(), Arc::downgrade(&circuit_breaker));
+        metrics::circuit_breaker_state(base_url, CircuitState::Closed.as_str());
+
+        circuit_breaker
+    }
+
+    /// A permit for a call to go upstream, or `None` while the circuit is open. While half
+    /// open only one trial call is allowed, until its permit is recorded or dropped.
+    pub fn allow(&self) -> Option<Permit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+
+        let trial = match inner.state {
+            CircuitState::Closed => false,
+            CircuitState::Open => {
+                let elapsed = inner.opened_at.map(|opened_at| opened_at.elapsed());
+                if matches!(elapsed, Some(elapsed) if elapsed < self.options.open_duration) {
+                    return None;
+                }
+                self.transition(&mut inner, CircuitState::HalfOpen);
+                true
+            }
+            CircuitState::HalfOpen if inner.trial_in_flight => return None,
+            CircuitState::HalfOpen => true,
+        };
+        inner.trial_in_flight |= trial;
+
+        Some(Permit {
+            circuit_breaker: self,
+            trial,
+            recorded: false,
+        })
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.consecutive_failures = 0;
+        inner.trial_in_flight = false;
+        self.transition(&mut inner, CircuitState::Closed);
+    }
+
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.consecutive_failures += 1;
+        inner.trial_in_flight = false;
+
+        if inner.state == CircuitState::HalfOpen
+            || inner.consecutive_failures >= self.options.failure_threshold
+        {
+            inner.opened_at = Some(Instant::now());
+            self.transition(&mut inner, CircuitState::Open);
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.inner.lock().unwrap().state
+    }
+
+    fn transition(&self, inner: &mut Inner, state: CircuitState) {
+        if inner.state != state {
+            log::warn!("circuit for {} is now {}", self.base_url, state.as_str());
+            inner.state = state;
+            metrics::circuit_breaker_state(&self.base_url, state.as_str());
+        }
+    }
+}
+
+/// Lets one call through the breaker. A permit dropped without an outcome, e.g. because the
+/// caller went away mid-request, counts as neither and frees the trial of a half open circuit.
+pub struct Permit<'a> {
+    circuit_breaker: &'a CircuitBreaker,
+    trial: bool,
+    recorded: bool,
+}
+
+impl Permit<'_> {
+    pub fn record_success(mut self) {
+        self.recorded = true;
+        self.circuit_breaker.record_success();
+    }
+
+    pub fn record_failure(mut self) {
+        self.recorded = true;
+        self.circuit_breaker.record_failure();
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.trial && !self.recorded {
+            self.circuit_breaker.inner.lock().unwrap().trial_in_flight = false;
+        }
+    }
+}
+
+/// State of every live circuit breaker, keyed by base url.
+pub fn circuit_states() -> Vec<(String, CircuitState)> {
+    let mut circuit_breakers = CIRCUIT_BREAKERS.lock().unwrap();
+
+    circuit_breakers.retain(|_, circuit_breaker| circuit_breaker.strong_count() > 0);
+
+    let mut states: Vec<_> = circuit_breakers
+        .iter()
+        .filter_map(|(base_url, circuit_breaker)| {
+            circuit_breaker
+                .upgrade()
+                .map(|circuit_breaker| (base_url.clone(), circuit_breaker.state()))
+        })
+        .collect();
+    states.sort_by(|a, b| a.0.cmp(&b.0));
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let circuit_breaker = CircuitBreaker::register(
+            "http://circuit-breaker.test/threshold",
+            CircuitBreakerOptions {
+                failure_threshold: 3,
+                open_duration: Duration::from_secs(60),
+            },
+        );
+
+        circuit_breaker.record_failure();
+        circuit_breaker.record_failure();
+        circuit_breaker.record_success();
+        circuit_breaker.record_failure();
+        circuit_breaker.record_failure();
+        assert_eq!(circuit_breaker.state(), CircuitState::Closed);
+        assert!(circuit_breaker.allow().is_some());
+
+        circuit_breaker.record_failure();
+        assert_eq!(circuit_breaker.state(), CircuitState::Open);
+        assert!(circuit_breaker.allow().is_none());
+
+        assert!(circuit_states().contains(&(
+            "http://circuit-breaker.test/threshold".to_owned(),
+            CircuitState::Open
+        )));
+    }
+
+    #[test]
+    fn test_half_open_allows_single_trial() {
+        let circuit_breaker = CircuitBreaker::register(
+            "http://circuit-breaker.test/half-open",
+            CircuitBreakerOptions {
+                failure_threshold: 1,
+                open_duration: Duration::from_secs(0),
+            },
+        );
+
+        circuit_breaker.record_failure();
+        assert_eq!(circuit_breaker.state(), CircuitState::Open);
+
+        let trial = circuit_breaker.allow().unwrap();
+        assert_eq!(circuit_breaker.state(), CircuitState::HalfOpen);
+        assert!(circuit_breaker.allow().is_none());
+
+        trial.record_failure();
+        assert_eq!(circuit_breaker.state(), CircuitState::Open);
+
+        circuit_breaker.allow().unwrap().record_success();
+        assert_eq!(circuit_breaker.state(), CircuitState::Closed);
+        assert!(circuit_breaker.allow().is_some());
+    }
+
+    #[test]
+    fn test_dropped_trial_frees_half_open() {
+        let circuit_breaker = CircuitBreaker::register(
+            "http://circuit-breaker.test/dropped-trial",
+            CircuitBreakerOptions {
+                failure_threshold: 1,
+                open_duration: Duration::from_secs(0),
+            },
+        );
+
+        circuit_breaker.record_failure();
+
+        let trial = circuit_breaker.allow().unwrap();
+        assert!(circuit_breaker.allow().is_none());
+
+        drop(trial);
+        assert_eq!(circuit_breaker.state(), CircuitState::HalfOpen);
+
+        circuit_breaker.allow().unwrap().record_success();
+        assert_eq!(circuit_breaker.state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::errors::{RustKataError, RustKataResult};
+use crate::observability::metrics;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimiterOptions {
+    /// Tokens added to the bucket every second.
+    pub requests_per_second: f64,
+    /// Tokens the bucket holds, i.e. how many requests may go out back to back.
+    pub burst: u32,
+    /// Requests allowed to wait for a token at once. Any more are turned away.
+    pub max_queue: usize,
+    /// Longest a request waits for a token before it is turned away.
+    pub max_wait: Duration,
+}
+
+impl Default for RateLimiterOptions {
+    /// The crates.io data access policy asks crawlers for at most one request per second.
+    fn default() -> Self {
+        RateLimiterOptions {
+            requests_per_second: 1.0,
+            burst: 1,
+            max_queue: 100,
+            max_wait: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Token bucket for requests to a single base url.
+///
+/// Waiting requests reserve their token up front, leaving the bucket in debt, so they are
+/// let through in the order they arrived without waking each other up.
+pub struct RateLimiter {
+    base_url: String,
+    options: RateLimiterOptions,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    queued: usize,
+}
+
+impl RateLimiter {
+    pub fn new(base_url: &str, options: RateLimiterOptions) -> Self {
+        RateLimiter {
+            base_url: base_url.to_owned(),
+            bucket: Mutex::new(Bucket {
+                tokens: options.burst as f64,
+                refilled_at: Instant::now(),
+                queued: 0,
+            }),
+            options,
+        }
+    }
+
+    pub fn options(&self) -> &RateLimiterOptions {
+        &self.options
+    }
+
+    /// Waits for a token, or fails with `RustKataError::RateLimited` when the queue is full
+    /// or the token would not arrive within `max_wait`.
+    pub async fn acquire(&self) -> RustKataResult<()> {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+
+            let now = Instant::now();
+            bucket.tokens = (bucket.tokens
+                + now.duration_since(bucket.refilled_at).as_secs_f64()
+                    * self.options.requests_per_second)
+                .min(self.options.burst as f64);
+            bucket.refilled_at = now;
+
+            let wait = if bucket.tokens >= 1.0 {
+                Duration::from_secs(0)
+            } else {
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.options.requests_per_second)
+            };
+
+            if wait > Duration::from_secs(0) {
+                let reason = if bucket.queued >= self.options.max_queue {
+                    Some("queue_full")
+                } else if wait > self.options.max_wait {
+                    Some("max_wait")
+                } else {
+                    None
+                };
+
+                if let Some(reason) = reason {
+                    metrics::rate_limiter_rejected_count(&self.base_url, reason);
+                    return Err(RustKataError::RateLimited { retry_after: wait });
+                }
+
+                bucket.queued += 1;
+                metrics::rate_limiter_queue_depth(&self.base_url).set(bucket.queued as i64);
+            }
+
+            bucket.tokens -= 1.0;
+            wait
+        };
+
+        metrics::rate_limiter_wait_seconds(&self.base_url).observe(wait.as_secs_f64());
+
+        if wait > Duration::from_secs(0) {
+            let _queued = Queued(self);
+            actix_web::rt::time::sleep(wait).await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Leaves the queue once waiting is over, even when the waiting request is dropped.
+struct Queued<'a>(&'a RateLimiter);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        let mut bucket = self.0.bucket.lock().unwrap();
+        bucket.queued -= 1;
+        metrics::rate_limiter_queue_depth(&self.0.base_url).set(bucket.queued as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_burst_then_paced() {
+        let rate_limiter = RateLimiter::new(
+            "http://rate-limiter.test/paced",
+            RateLimiterOptions {
+                requests_per_second: 50.0,
+                burst: 2,
+                max_queue: 10,
+                max_wait: Duration::from_secs(1),
+            },
+        );
+
+        let instant = Instant::now();
+
+        for _ in 0..4 {
+            rate_limiter.acquire().await.unwrap();
+        }
+
+        // the first two go out immediately, the next two 20ms apart
+        assert!(instant.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[actix_rt::test]
+    async fn test_max_wait_exceeded() {
+        let rate_limiter = RateLimiter::new(
+            "http://rate-limiter.test/max-wait",
+            RateLimiterOptions {
+                requests_per_second: 1.0,
+                burst: 1,
+                max_queue: 10,
+                max_wait: Duration::from_millis(100),
+            },
+        );
+
+        rate_limiter.acquire().await.unwrap();
+
+        match rate_limiter.acquire().await {
+            Err(RustKataError::RateLimited { retry_after }) => {
+                assert!(retry_after > Duration::from_millis(100))
+            }
+            result => panic!("expected rate limited, got {:?}", result),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_queue_full() {
+        let rate_limiter = RateLimiter::new(
+            "http://rate-limiter.test/queue-full",
+            RateLimiterOptions {
+                requests_per_second: 20.0,
+                burst: 1,
+                max_queue: 1,
+                max_wait: Duration::from_secs(1),
+            },
+        );
+
+        let (a, b, c) = futures::join!(
+            rate_limiter.acquire(),
+            rate_limiter.acquire(),
+            rate_limiter.acquire(),
+        );
+
+        assert!(a.is_ok());
+        assert!(b.is_ok());
+        assert!(matches!(c, Err(RustKataError::RateLimited { .. })));
+    }
+}
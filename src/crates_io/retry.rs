@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct RetryOptions {
+    /// Attempts made after the first one fails.
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled on every following one.
+    pub base_delay: Duration,
+    /// Upper bound on any single wait, including one asked for by `Retry-After`.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        RetryOptions {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryOptions {
+    /// Exponential backoff with full jitter for the given retry, counting from zero.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .checked_mul(2u32.saturating_pow(retry))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        ceiling.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+/// Status codes worth asking again for.
+pub fn retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// `Retry-After` as either delay seconds or an HTTP date.
+pub fn retry_after(response: &reqwest::Response, now: DateTime<Utc>) -> Option<Duration> {
+    let value = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    DateTime::parse_from_rfc2822(value).ok().map(|date| {
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_bounded() {
+        let options = RetryOptions {
+            max_retries: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+        };
+
+        for retry in 0..40 {
+            let ceiling =
+                Duration::from_millis(100 * 2u64.pow(retry.min(5))).min(Duration::from_millis(350));
+            assert!(options.backoff(retry) <= ceiling);
+        }
+    }
+
+    #[test]
+    fn test_retryable() {
+        assert!(retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(retryable(StatusCode::BAD_GATEWAY));
+        assert!(retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!retryable(StatusCode::NOT_FOUND));
+        assert!(!retryable(StatusCode::NOT_MODIFIED));
+    }
+}
@@ -1,7 +1,15 @@
-use crate::dependency_graph::models::{Edge, Node, QueryParams, QueryResult};
-use crate::interfaces::crate_registry::CrateRegistry;
-use crate::interfaces::http;
+use crate::dependency_graph::formats;
+use crate::dependency_graph::models::{
+    BatchQueryParams, DoneEvent, ErrorEvent, Event, Format, QueryParams, QueryResult, RootError,
+};
+use crate::dependency_graph::resolver::Resolver;
+use crate::errors::RustKataResult;
+use crate::interfaces::{http, validation};
+use crate::observability::metrics;
+use crate::registry::registries::Registries;
 use actix_web::{web, HttpResponse};
+use futures::channel::mpsc;
+use futures::{SinkExt, Stream, StreamExt};
 
 pub fn config(cfg: &mut web::ServiceConfig, host_base_path: &str) {
     let path = format!("{}/dependency-graph", host_base_path);
@@ -9,37 +17,190 @@ pub fn config(cfg: &mut web::ServiceConfig, host_base_path: &str) {
     cfg.service(
         web::scope(&path)
             .app_data(http::query_config())
-            .route("", web::get().to(query)),
+            .app_data(http::json_config())
+            .route("", web::get().to(query))
+            .route("", web::post().to(batch_query))
+            .route("/stream", web::get().to(stream)),
     );
 }
 
 async fn query(
     web::Query(query_parameters): web::Query<QueryParams>,
-    crates_io_client: web::Data<Box<dyn CrateRegistry>>,
-) -> HttpResponse {
-    let result = crates_io_client
-        .get_crate_dependencies(&query_parameters.name, &query_parameters.version)
-        .await
-        .unwrap();
-
-    let edges = result
-        .dependencies
-        .iter()
-        .map(|dependency| Edge {
-            relationship: dependency.kind.to_owned(),
-            node: Node {
-                name: dependency.crate_id.to_owned(),
-                version: dependency.req.to_owned(),
-                edges: None,
-            },
-        })
-        .collect();
-
-    HttpResponse::Ok().json(QueryResult {
-        data: Some(vec![Node {
-            name: query_parameters.name.to_owned(),
-            version: query_parameters.version.to_owned(),
-            edges: Some(edges),
-        }]),
+    registries: web::Data<Registries>,
+) -> RustKataResult<HttpResponse> {
+    if let Some(registry) = registries.unknown(query_parameters.registry.as_deref()) {
+        return Ok(http::unknown_registry(registry));
+    }
+
+    Ok(match query_parameters.format.unwrap_or(Format::Tree) {
+        Format::Tree => tree(query_parameters, registries).await?,
+        Format::EdgeList => HttpResponse::Ok()
+            .content_type("application/x-ndjson")
+            .streaming(bytes(formats::edge_list(events(
+                query_parameters,
+                registries,
+            )))),
+        Format::NodesAndEdges => HttpResponse::Ok()
+            .content_type("application/json")
+            .streaming(bytes(formats::nodes_and_edges(events(
+                query_parameters,
+                registries,
+            )))),
     })
 }
+
+async fn tree(
+    query_parameters: QueryParams,
+    registries: web::Data<Registries>,
+) -> RustKataResult<HttpResponse> {
+    let mut resolver = Resolver::new(&registries);
+
+    let node = resolver
+        .resolve(
+            query_parameters.registry.as_deref(),
+            &query_parameters.name,
+            &query_parameters.version,
+            validation::depth(query_parameters.depth),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(QueryResult {
+        data: Some(vec![node]),
+        errors: None,
+    }))
+}
+
+/// Resolves every root into one combined view. The roots share one resolver, so that
+/// subtrees they have in common are resolved once, and are therefore resolved in turn. A
+/// root that fails is reported in `errors` while the others are still returned.
+async fn batch_query(
+    web::Json(batch_query_parameters): web::Json<BatchQueryParams>,
+    registries: web::Data<Registries>,
+) -> RustKataResult<HttpResponse> {
+    validation::roots(batch_query_parameters.roots.len())?;
+
+    for root in &batch_query_parameters.roots {
+        if let Some(registry) = registries.unknown(batch_query_parameters.registry(root)) {
+            return Ok(http::unknown_registry(registry));
+        }
+    }
+
+    let mut resolver = Resolver::new(&registries);
+
+    let depth = validation::depth(batch_query_parameters.depth);
+
+    let mut nodes = Vec::with_capacity(batch_query_parameters.roots.len());
+    let mut errors = Vec::new();
+    for root in &batch_query_parameters.roots {
+        match resolver
+            .resolve(
+                batch_query_parameters.registry(root),
+                &root.name,
+                &root.version,
+                depth,
+            )
+            .await
+        {
+            Ok(node) => nodes.push(node),
+            Err(err) => {
+                metrics::error_count(err.kind());
+                errors.push(RootError {
+                    name: root.name.to_owned(),
+                    version: root.version.to_owned(),
+                    code: err.kind().to_owned(),
+                    description: err.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(QueryResult {
+        data: Some(nodes),
+        errors: match errors.is_empty() {
+            true => None,
+            false => Some(errors),
+        },
+    }))
+}
+
+/// Streams the resolution as server-sent events: a `node` or `edge` event for every
+/// discovery, an `error` event if resolution fails, and a closing `done` summary.
+async fn stream(
+    web::Query(query_parameters): web::Query<QueryParams>,
+    registries: web::Data<Registries>,
+) -> HttpResponse {
+    if let Some(registry) = registries.unknown(query_parameters.registry.as_deref()) {
+        return http::unknown_registry(registry);
+    }
+
+    let events = futures::stream::unfold(
+        Some((events(query_parameters, registries), 0, 0)),
+        |state| async move {
+            let (mut events, mut nodes, mut edges) = state?;
+
+            match events.next().await {
+                Some(event) => {
+                    match &event {
+                        Event::Node(_) => nodes += 1,
+                        Event::Edge(_) => edges += 1,
+                        _ => {}
+                    }
+                    Some((event, Some((events, nodes, edges))))
+                }
+                None => Some((Event::Done(DoneEvent { nodes, edges }), None)),
+            }
+        },
+    );
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .streaming(bytes(events.map(|event| {
+            web::Bytes::from(format!(
+                "event: {}\ndata: {}\n\n",
+                event.name(),
+                event.data()
+            ))
+        })))
+}
+
+/// Events resolution may run ahead of the reader by, before it waits for it.
+const EVENT_BUFFER: usize = 64;
+
+/// Resolves the query in the background, reporting discoveries on the returned channel.
+/// The channel closes once resolution finishes, after an `error` event if it failed.
+fn events(
+    query_parameters: QueryParams,
+    registries: web::Data<Registries>,
+) -> mpsc::Receiver<Event> {
+    let (mut sender, receiver) = mpsc::channel(EVENT_BUFFER);
+
+    actix_web::rt::spawn(async move {
+        let mut resolver = Resolver::new(&registries).events(sender.clone());
+
+        if let Err(err) = resolver
+            .resolve(
+                query_parameters.registry.as_deref(),
+                &query_parameters.name,
+                &query_parameters.version,
+                validation::depth(query_parameters.depth),
+            )
+            .await
+        {
+            metrics::error_count(err.kind());
+            let _ = sender
+                .send(Event::Error(ErrorEvent {
+                    description: err.to_string(),
+                }))
+                .await;
+        }
+    });
+
+    receiver
+}
+
+fn bytes<S>(stream: S) -> impl Stream<Item = Result<web::Bytes, actix_web::Error>> + Unpin + 'static
+where
+    S: Stream<Item = web::Bytes> + 'static,
+{
+    Box::pin(stream.map(Ok))
+}
@@ -0,0 +1,133 @@
+use crate::dependency_graph::models::{AdjacencyEdge, AdjacencyNode, EdgeEvent, EdgeRecord, Event};
+use actix_web::web::Bytes;
+use futures::{Stream, StreamExt};
+use std::collections::HashMap;
+
+/// Newline delimited `EdgeRecord`s, written as the resolver discovers them.
+pub fn edge_list<S>(events: S) -> impl Stream<Item = Bytes>
+where
+    S: Stream<Item = Event>,
+{
+    events.filter_map(|event| async move {
+        let line = match event {
+            Event::Edge(edge) => serde_json::to_string(&EdgeRecord::from(edge)),
+            Event::Error(error) => serde_json::to_string(&error),
+            _ => return None,
+        }
+        .unwrap();
+
+        Some(Bytes::from(line + "\n"))
+    })
+}
+
+/// An `AdjacencyResult` document. Edges are written as they are discovered and refer to
+/// nodes by id; the node table is only small, so it is kept back and written last.
+pub fn nodes_and_edges<S>(events: S) -> impl Stream<Item = Bytes>
+where
+    S: Stream<Item = Event> + Unpin,
+{
+    let edges = futures::stream::unfold(Some((events, Adjacency::default())), |state| async move {
+        let (mut events, mut adjacency) = state?;
+
+        loop {
+            match events.next().await {
+                Some(Event::Node(node)) => {
+                    adjacency.id(&node.name, &node.version);
+                }
+                Some(Event::Edge(edge)) => {
+                    let chunk = adjacency.edge(edge);
+                    return Some((chunk, Some((events, adjacency))));
+                }
+                Some(Event::Error(error)) => adjacency.error = Some(error.description),
+                Some(Event::Done(_)) => {}
+                None => return Some((adjacency.close(), None)),
+            }
+        }
+    });
+
+    futures::stream::once(async { Bytes::from_static(b"{\"edges\":[") }).chain(edges)
+}
+
+#[derive(Default)]
+struct Adjacency {
+    ids: HashMap<(String, String), usize>,
+    nodes: Vec<AdjacencyNode>,
+    edges: usize,
+    error: Option<String>,
+}
+
+impl Adjacency {
+    fn id(&mut self, name: &str, version: &str) -> usize {
+        let nodes = &mut self.nodes;
+
+        *self
+            .ids
+            .entry((name.to_owned(), version.to_owned()))
+            .or_insert_with(|| {
+                nodes.push(AdjacencyNode {
+                    id: nodes.len(),
+                    name: name.to_owned(),
+                    version: version.to_owned(),
+                });
+                nodes.len() - 1
+            })
+    }
+
+    fn edge(&mut self, edge: EdgeEvent) -> Bytes {
+        let from = self.id(&edge.from, &edge.from_version);
+        let to = self.id(&edge.to, edge.to_version.as_ref().unwrap_or(&edge.req));
+
+        let separator = if self.edges == 0 { "" } else { "," };
+        self.edges += 1;
+
+        let edge = AdjacencyEdge {
+            from,
+            to,
+            req: edge.req,
+            kind: edge.kind,
+            optional: edge.optional,
+            target: edge.target,
+            error: edge.error,
+        };
+
+        Bytes::from(format!(
+            "{}{}",
+            separator,
+            serde_json::to_string(&edge).unwrap()
+        ))
+    }
+
+    fn close(self) -> Bytes {
+        let mut chunk = format!(
+            "],\"nodes\":{}",
+            serde_json::to_string(&self.nodes).unwrap()
+        );
+
+        if let Some(error) = self.error {
+            chunk.push_str(&format!(
+                ",\"error\":{}",
+                serde_json::to_string(&error).unwrap()
+            ));
+        }
+
+        chunk.push('}');
+
+        Bytes::from(chunk)
+    }
+}
+
+impl From<EdgeEvent> for EdgeRecord {
+    fn from(edge: EdgeEvent) -> Self {
+        EdgeRecord {
+            from: edge.from,
+            from_version: edge.from_version,
+            to: edge.to,
+            // unresolved leaves carry their requirement, as in the tree format
+            to_version: edge.to_version.unwrap_or(edge.req),
+            kind: edge.kind,
+            optional: edge.optional,
+            target: edge.target,
+            error: edge.error,
+        }
+    }
+}
@@ -1,2 +1,5 @@
 pub mod endpoints;
+pub mod formats;
 pub mod models;
+pub mod requirements;
+pub mod resolver;
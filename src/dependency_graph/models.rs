@@ -1,20 +1,104 @@
+use crate::interfaces::validation;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
 pub struct QueryParams {
-    #[serde(rename = "name")]
+    #[serde(
+        rename = "name",
+        deserialize_with = "validation::deserialize_crate_name"
+    )]
     pub name: String,
-    #[serde(rename = "version")]
+    /// Exact version, or `latest` for the highest non-yanked stable version.
+    #[serde(
+        rename = "version",
+        deserialize_with = "validation::deserialize_version"
+    )]
     pub version: String,
+    /// Levels of dependencies to resolve, `1` by default and at most `validation::MAX_DEPTH`.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "depth")]
+    pub depth: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "format")]
+    pub format: Option<Format>,
+    /// Name of the registry the crate is published to. Defaults to the default registry.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub registry: Option<String>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Format {
+    #[serde(rename = "tree")]
+    Tree,
+    #[serde(rename = "edgelist")]
+    EdgeList,
+    // `+` arrives as a space unless the client percent-encodes it
+    #[serde(rename = "nodes+edges", alias = "nodes edges")]
+    NodesAndEdges,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BatchQueryParams {
+    /// At most `validation::MAX_ROOTS`.
+    #[serde(rename = "roots")]
+    pub roots: Vec<BatchRoot>,
+    /// Levels of dependencies to resolve, `1` by default and at most `validation::MAX_DEPTH`.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "depth")]
+    pub depth: Option<usize>,
+    /// Registry of the roots that do not name their own.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub registry: Option<String>,
+}
+
+impl BatchQueryParams {
+    /// Name of the registry `root` is looked up in.
+    pub fn registry<'a>(&'a self, root: &'a BatchRoot) -> Option<&'a str> {
+        root.registry.as_deref().or(self.registry.as_deref())
+    }
+}
+
+/// Root of a batch query. Depth is the batch's, and only trees are returned, so a root
+/// naming either is rejected rather than ignored.
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchRoot {
+    #[serde(
+        rename = "name",
+        deserialize_with = "validation::deserialize_crate_name"
+    )]
+    pub name: String,
+    /// Exact version, or `latest` for the highest non-yanked stable version.
+    #[serde(
+        rename = "version",
+        deserialize_with = "validation::deserialize_version"
+    )]
+    pub version: String,
+    /// Name of the registry the crate is published to. Defaults to the batch's registry.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub registry: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct QueryResult {
     #[serde(skip_serializing_if = "Option::is_none", rename = "data")]
     pub data: Option<Vec<Node>>,
+    /// Roots of a batch query that could not be resolved, while the others are in `data`.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "errors")]
+    pub errors: Option<Vec<RootError>>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RootError {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "version")]
+    pub version: String,
+    /// Short, stable name of the error, e.g. `not_found`.
+    #[serde(rename = "code")]
+    pub code: String,
+    #[serde(rename = "description")]
+    pub description: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Node {
     #[serde(rename = "name")]
     pub name: String,
@@ -22,12 +106,153 @@ pub struct Node {
     pub version: String,
     #[serde(skip_serializing_if = "Option::is_none", rename = "edges")]
     pub edges: Option<Vec<Edge>>,
+    /// Backend of a fallback chain the node's dependencies came from.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "served_by")]
+    pub served_by: Option<String>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Edge {
     #[serde(rename = "relationship")]
     pub relationship: String,
     #[serde(rename = "node")]
     pub node: Node,
+    /// Why the dependency could not be resolved, leaving its node a leaf.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "error")]
+    pub error: Option<String>,
+}
+
+/// Discoveries reported by the resolver while it walks a graph.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Node(NodeEvent),
+    Edge(EdgeEvent),
+    Error(ErrorEvent),
+    Done(DoneEvent),
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Node(_) => "node",
+            Event::Edge(_) => "edge",
+            Event::Error(_) => "error",
+            Event::Done(_) => "done",
+        }
+    }
+
+    pub fn data(&self) -> String {
+        match self {
+            Event::Node(event) => serde_json::to_string(event),
+            Event::Edge(event) => serde_json::to_string(event),
+            Event::Error(event) => serde_json::to_string(event),
+            Event::Done(event) => serde_json::to_string(event),
+        }
+        .unwrap()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NodeEvent {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "version")]
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "served_by")]
+    pub served_by: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EdgeEvent {
+    #[serde(rename = "from")]
+    pub from: String,
+    #[serde(rename = "from_version")]
+    pub from_version: String,
+    #[serde(rename = "to")]
+    pub to: String,
+    #[serde(rename = "to_version")]
+    pub to_version: Option<String>,
+    #[serde(rename = "req")]
+    pub req: String,
+    #[serde(rename = "kind")]
+    pub kind: String,
+    #[serde(rename = "optional")]
+    pub optional: bool,
+    #[serde(rename = "target")]
+    pub target: Option<String>,
+    /// Why the dependency could not be resolved, leaving it without `to_version`.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "error")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ErrorEvent {
+    #[serde(rename = "description")]
+    pub description: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DoneEvent {
+    #[serde(rename = "nodes")]
+    pub nodes: usize,
+    #[serde(rename = "edges")]
+    pub edges: usize,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct EdgeRecord {
+    #[serde(rename = "from")]
+    pub from: String,
+    #[serde(rename = "from_version")]
+    pub from_version: String,
+    #[serde(rename = "to")]
+    pub to: String,
+    #[serde(rename = "to_version")]
+    pub to_version: String,
+    #[serde(rename = "kind")]
+    pub kind: String,
+    #[serde(rename = "optional")]
+    pub optional: bool,
+    #[serde(rename = "target")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "error")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AdjacencyResult {
+    #[serde(rename = "edges")]
+    pub edges: Vec<AdjacencyEdge>,
+    #[serde(rename = "nodes")]
+    pub nodes: Vec<AdjacencyNode>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "error")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AdjacencyNode {
+    #[serde(rename = "id")]
+    pub id: usize,
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "version")]
+    pub version: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AdjacencyEdge {
+    #[serde(rename = "from")]
+    pub from: usize,
+    #[serde(rename = "to")]
+    pub to: usize,
+    #[serde(rename = "req")]
+    pub req: String,
+    #[serde(rename = "kind")]
+    pub kind: String,
+    #[serde(rename = "optional")]
+    pub optional: bool,
+    #[serde(rename = "target")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "error")]
+    pub error: Option<String>,
 }
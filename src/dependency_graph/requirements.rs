@@ -0,0 +1,135 @@
+use crate::interfaces::crate_registry::get_crate::VersionResponse;
+use semver::{Version, VersionReq};
+use std::collections::{HashMap, HashSet};
+
+/// Highest non-yanked version satisfying the requirement, as cargo would select it.
+pub fn matching_version<'a>(req: &str, versions: &'a [VersionResponse]) -> Option<&'a str> {
+    let req = VersionReq::parse(req).ok()?;
+
+    versions
+        .iter()
+        .filter(|version| !version.yanked)
+        .filter_map(|version| {
+            Version::parse(&version.num)
+                .ok()
+                .map(|parsed| (parsed, version.num.as_str()))
+        })
+        .filter(|(parsed, _)| req.matches(parsed))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, num)| num)
+}
+
+/// Names of optional dependencies switched on by the requested features.
+pub fn activated_dependencies(
+    features: &HashMap<String, Vec<String>>,
+    requested: &[String],
+) -> HashSet<String> {
+    let mut activated = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut pending = requested.to_vec();
+
+    while let Some(feature) = pending.pop() {
+        if !visited.insert(feature.clone()) {
+            continue;
+        }
+
+        match features.get(&feature) {
+            Some(values) => {
+                for value in values {
+                    if let Some(dependency) = value.strip_prefix("dep:") {
+                        activated.insert(dependency.to_owned());
+                    } else if let Some((dependency, _)) = value.split_once('/') {
+                        activated.insert(dependency.trim_end_matches('?').to_owned());
+                    } else if features.contains_key(value) {
+                        pending.push(value.to_owned());
+                    } else {
+                        activated.insert(value.to_owned());
+                    }
+                }
+            }
+            None => {
+                activated.insert(feature);
+            }
+        }
+    }
+
+    activated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::crate_registry::get_crate::VersionLinksResponse;
+
+    #[test]
+    fn test_matching_version() {
+        let versions = vec![
+            version("0.7.3", false),
+            version("0.8.0", false),
+            version("0.8.2", false),
+            version("0.8.3", true),
+        ];
+
+        assert_eq!(matching_version("^0.8", &versions), Some("0.8.2"));
+        assert_eq!(matching_version("^0.7.1", &versions), Some("0.7.3"));
+        assert_eq!(matching_version("*", &versions), Some("0.8.2"));
+        assert_eq!(matching_version("^1", &versions), None);
+        assert_eq!(matching_version("not a requirement", &versions), None);
+    }
+
+    #[test]
+    fn test_activated_dependencies() {
+        let mut features = HashMap::new();
+        features.insert("default".to_owned(), vec!["std".to_owned()]);
+        features.insert(
+            "std".to_owned(),
+            vec!["alloc".to_owned(), "rand_core/std".to_owned()],
+        );
+        features.insert("alloc".to_owned(), vec![]);
+        features.insert("serde1".to_owned(), vec!["dep:serde".to_owned()]);
+        features.insert("log".to_owned(), vec!["rand_core?/log".to_owned()]);
+
+        let activated = activated_dependencies(&features, &["default".to_owned()]);
+        assert_eq!(
+            activated,
+            vec!["rand_core".to_owned()].into_iter().collect()
+        );
+
+        let activated =
+            activated_dependencies(&features, &["serde1".to_owned(), "libc".to_owned()]);
+        assert_eq!(
+            activated,
+            vec!["serde".to_owned(), "libc".to_owned()]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    fn version(num: &str, yanked: bool) -> VersionResponse {
+        VersionResponse {
+            id: 0,
+            crate_: "rand".to_owned(),
+            num: num.to_owned(),
+            dl_path: String::new(),
+            readme_path: String::new(),
+            updated_at: String::new(),
+            created_at: String::new(),
+            downloads: 0,
+            features: HashMap::new(),
+            yanked,
+            license: None,
+            links: VersionLinksResponse {
+                dependencies: String::new(),
+                version_downloads: String::new(),
+                authors: String::new(),
+                extra: Default::default(),
+            },
+            crate_size: None,
+            published_by: None,
+            audit_actions: vec![],
+            checksum: None,
+            rust_version: None,
+            extra: Default::default(),
+        }
+    }
+}
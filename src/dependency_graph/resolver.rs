@@ -0,0 +1,351 @@
+use crate::dependency_graph::models::{Edge, EdgeEvent, Event, Node, NodeEvent};
+use crate::dependency_graph::requirements::matching_version;
+use crate::errors::{RustKataError, RustKataResult};
+use crate::interfaces::crate_registry::{get_crate, get_crate_dependencies, CrateRegistry};
+use crate::interfaces::validation::LATEST;
+use crate::registry::registries::Registries;
+use futures::channel::mpsc::Sender;
+use futures::future::BoxFuture;
+use futures::SinkExt;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct Progress {
+    nodes_resolved: AtomicUsize,
+    pending_registry_calls: AtomicUsize,
+}
+
+impl Progress {
+    pub fn nodes_resolved(&self) -> usize {
+        self.nodes_resolved.load(Ordering::Relaxed)
+    }
+
+    pub fn pending_registry_calls(&self) -> usize {
+        self.pending_registry_calls.load(Ordering::Relaxed)
+    }
+}
+
+/// Resolves dependency graphs against a registry, fetching every crate and every
+/// `(crate, version)` at most once for the lifetime of the resolver.
+///
+/// Dependencies referring to another registry by its index url are looked up there, and
+/// their own dependencies in turn. References to registries that are not configured are
+/// left unresolved.
+///
+/// Children are pinned to the highest version matching their requirement and walked up to
+/// the requested depth. Dev-dependencies of children are not followed, as cargo does not
+/// build them.
+///
+/// A child that cannot be looked up keeps its requirement as version and carries the error
+/// on its edge, rather than failing the whole graph. Only a failing root fails resolution.
+///
+/// When given an event sink, every `(crate, version)` is reported once as a node together
+/// with its edges, in the order they are discovered. Resolution waits for the sink to make
+/// room, so a slow reader slows it down rather than piling events up.
+pub struct Resolver<'a> {
+    registries: &'a Registries,
+    progress: Arc<Progress>,
+    events: Option<Sender<Event>>,
+    emitted: HashSet<(String, String, String)>,
+    crates: HashMap<(String, String), Arc<get_crate::Response>>,
+    dependencies: HashMap<(String, String, String), Arc<get_crate_dependencies::Response>>,
+    edges: HashMap<(String, String, String, usize), Vec<Edge>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(registries: &'a Registries) -> Self {
+        Resolver::with_progress(registries, Arc::new(Progress::default()))
+    }
+
+    pub fn with_progress(registries: &'a Registries, progress: Arc<Progress>) -> Self {
+        Resolver {
+            registries,
+            progress,
+            events: None,
+            emitted: HashSet::new(),
+            crates: HashMap::new(),
+            dependencies: HashMap::new(),
+            edges: HashMap::new(),
+        }
+    }
+
+    pub fn events(mut self, events: Sender<Event>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Resolves a root published to the registry called `registry`, or the default one.
+    /// A `latest` version is looked up first, and the node carries the version found.
+    pub async fn resolve(
+        &mut self,
+        registry: Option<&str>,
+        name: &str,
+        version: &str,
+        depth: usize,
+    ) -> RustKataResult<Node> {
+        let registry = registry
+            .unwrap_or_else(|| self.registries.default_name())
+            .to_owned();
+        let version = match version {
+            LATEST => {
+                let crate_ = self.crate_(&registry, name).await?;
+                matching_version("*", &crate_.versions)
+                    .ok_or(RustKataError::NotFound)?
+                    .to_owned()
+            }
+            _ => version.to_owned(),
+        };
+        let version = version.as_str();
+        let edges = self.edges(&registry, name, version, depth).await?;
+
+        Ok(Node {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            edges,
+            served_by: self.served_by(&registry, name, version),
+        })
+    }
+
+    fn edges<'b>(
+        &'b mut self,
+        registry: &'b str,
+        name: &'b str,
+        version: &'b str,
+        depth: usize,
+    ) -> BoxFuture<'b, RustKataResult<Option<Vec<Edge>>>> {
+        Box::pin(async move {
+            if depth == 0 {
+                return Ok(None);
+            }
+
+            let key = (
+                registry.to_owned(),
+                name.to_owned(),
+                version.to_owned(),
+                depth,
+            );
+            if let Some(edges) = self.edges.get(&key) {
+                return Ok(Some(edges.clone()));
+            }
+
+            let dependencies = self.dependencies(registry, name, version).await?;
+
+            let emit =
+                self.emitted
+                    .insert((registry.to_owned(), name.to_owned(), version.to_owned()));
+            if emit {
+                self.emit(Event::Node(NodeEvent {
+                    name: name.to_owned(),
+                    version: version.to_owned(),
+                    served_by: dependencies.served_by.to_owned(),
+                }))
+                .await;
+            }
+
+            let mut edges = Vec::with_capacity(dependencies.dependencies.len());
+            for dependency in &dependencies.dependencies {
+                let child_registry = self.dependency_registry(registry, dependency);
+                let (child_version, mut error) = match &child_registry {
+                    Some(child_registry) if depth > 1 && dependency.kind != "dev" => {
+                        match self.child_version(child_registry, dependency).await {
+                            Ok(child_version) => (child_version, None),
+                            Err(err) => (None, Some(self.edge_error(dependency, err))),
+                        }
+                    }
+                    _ => (None, None),
+                };
+
+                if emit {
+                    self.emit(Event::Edge(EdgeEvent {
+                        from: name.to_owned(),
+                        from_version: version.to_owned(),
+                        to: dependency.crate_id.to_owned(),
+                        to_version: child_version.to_owned(),
+                        req: dependency.req.to_owned(),
+                        kind: dependency.kind.to_owned(),
+                        optional: dependency.optional,
+                        target: dependency.target.to_owned(),
+                        error: error.to_owned(),
+                    }))
+                    .await;
+                }
+
+                let (children, served_by) = match (child_registry, &child_version) {
+                    (Some(child_registry), Some(child_version)) => {
+                        match self
+                            .edges(
+                                &child_registry,
+                                &dependency.crate_id,
+                                child_version,
+                                depth - 1,
+                            )
+                            .await
+                        {
+                            Ok(children) => (
+                                children,
+                                self.served_by(
+                                    &child_registry,
+                                    &dependency.crate_id,
+                                    child_version,
+                                ),
+                            ),
+                            Err(err) => {
+                                error = Some(self.edge_error(dependency, err));
+                                (None, None)
+                            }
+                        }
+                    }
+                    _ => (None, None),
+                };
+
+                edges.push(Edge {
+                    relationship: dependency.kind.to_owned(),
+                    node: Node {
+                        name: dependency.crate_id.to_owned(),
+                        // unresolved leaves carry their requirement instead
+                        version: child_version.unwrap_or_else(|| dependency.req.to_owned()),
+                        edges: children,
+                        served_by,
+                    },
+                    error,
+                });
+            }
+
+            self.progress.nodes_resolved.fetch_add(1, Ordering::Relaxed);
+            self.edges.insert(key, edges.clone());
+
+            Ok(Some(edges))
+        })
+    }
+
+    /// Backend the dependencies of `(crate, version)` came from, if they were fetched.
+    fn served_by(&self, registry: &str, name: &str, version: &str) -> Option<String> {
+        self.dependencies
+            .get(&(registry.to_owned(), name.to_owned(), version.to_owned()))
+            .and_then(|dependencies| dependencies.served_by.to_owned())
+    }
+
+    async fn emit(&mut self, event: Event) {
+        if let Some(events) = &mut self.events {
+            // the receiver going away only means nobody is listening any more
+            let _ = events.send(event).await;
+        }
+    }
+
+    /// Name of the registry a dependency is published to, which is the dependent's own
+    /// unless it refers to another one.
+    fn dependency_registry(
+        &self,
+        registry: &str,
+        dependency: &get_crate_dependencies::DependencyResponse,
+    ) -> Option<String> {
+        match &dependency.registry {
+            None => Some(registry.to_owned()),
+            Some(index_url) => {
+                let registry = self.registries.by_index(index_url);
+                if registry.is_none() {
+                    log::warn!(
+                        "{} refers to unknown registry {}",
+                        dependency.crate_id,
+                        index_url
+                    );
+                }
+                registry.map(str::to_owned)
+            }
+        }
+    }
+
+    fn crate_registry(&self, registry: &str) -> RustKataResult<&'a dyn CrateRegistry> {
+        self.registries
+            .get(Some(registry))
+            .ok_or(RustKataError::Internal)
+    }
+
+    /// Version a dependency resolves to, with its own dependencies fetched already, so that
+    /// a child that cannot be resolved fails before its edge is reported.
+    async fn child_version(
+        &mut self,
+        registry: &str,
+        dependency: &get_crate_dependencies::DependencyResponse,
+    ) -> RustKataResult<Option<String>> {
+        let crate_ = self.crate_(registry, &dependency.crate_id).await?;
+
+        let child_version = matching_version(&dependency.req, &crate_.versions).map(str::to_owned);
+        if let Some(child_version) = &child_version {
+            self.dependencies(registry, &dependency.crate_id, child_version)
+                .await?;
+        }
+
+        Ok(child_version)
+    }
+
+    /// A failing child is recorded on its edge, and the rest of the graph still resolved.
+    fn edge_error(
+        &self,
+        dependency: &get_crate_dependencies::DependencyResponse,
+        err: RustKataError,
+    ) -> String {
+        log::warn!(
+            "failed to resolve {} {}: {}",
+            dependency.crate_id,
+            dependency.req,
+            err
+        );
+        err.to_string()
+    }
+
+    async fn crate_(
+        &mut self,
+        registry: &str,
+        name: &str,
+    ) -> RustKataResult<Arc<get_crate::Response>> {
+        let key = (registry.to_owned(), name.to_owned());
+        if let Some(crate_) = self.crates.get(&key) {
+            return Ok(crate_.clone());
+        }
+
+        let crate_registry = self.crate_registry(registry)?;
+
+        self.progress
+            .pending_registry_calls
+            .fetch_add(1, Ordering::Relaxed);
+        let result = crate_registry.get_crate(name).await;
+        self.progress
+            .pending_registry_calls
+            .fetch_sub(1, Ordering::Relaxed);
+
+        let crate_ = Arc::new(result?);
+        self.crates.insert(key, crate_.clone());
+
+        Ok(crate_)
+    }
+
+    async fn dependencies(
+        &mut self,
+        registry: &str,
+        name: &str,
+        version: &str,
+    ) -> RustKataResult<Arc<get_crate_dependencies::Response>> {
+        let key = (registry.to_owned(), name.to_owned(), version.to_owned());
+        if let Some(dependencies) = self.dependencies.get(&key) {
+            return Ok(dependencies.clone());
+        }
+
+        let crate_registry = self.crate_registry(registry)?;
+
+        self.progress
+            .pending_registry_calls
+            .fetch_add(1, Ordering::Relaxed);
+        let result = crate_registry.get_crate_dependencies(name, version).await;
+        self.progress
+            .pending_registry_calls
+            .fetch_sub(1, Ordering::Relaxed);
+
+        let dependencies = Arc::new(result?);
+        self.dependencies.insert(key, dependencies.clone());
+
+        Ok(dependencies)
+    }
+}
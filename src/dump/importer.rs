@@ -0,0 +1,660 @@
+use crate::errors::{RustKataError, RustKataResult};
+use flate2::read::GzDecoder;
+use rusqlite::{params, Connection, Transaction};
+use serde::{Deserialize, Deserializer};
+use std::io::Read;
+use std::path::Path;
+
+pub(crate) const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS crates (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT,
+        homepage TEXT,
+        documentation TEXT,
+        repository TEXT,
+        downloads INTEGER NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE UNIQUE INDEX IF NOT EXISTS crates_name ON crates (name COLLATE NOCASE);
+    CREATE TABLE IF NOT EXISTS versions (
+        id INTEGER PRIMARY KEY,
+        crate_id INTEGER NOT NULL,
+        num TEXT NOT NULL,
+        checksum TEXT,
+        crate_size INTEGER,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        downloads INTEGER NOT NULL,
+        features TEXT NOT NULL,
+        license TEXT,
+        links TEXT,
+        published_by INTEGER,
+        rust_version TEXT,
+        yanked INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS versions_crate_id ON versions (crate_id);
+    CREATE TABLE IF NOT EXISTS dependencies (
+        id INTEGER PRIMARY KEY,
+        version_id INTEGER NOT NULL,
+        crate_id INTEGER NOT NULL,
+        req TEXT NOT NULL,
+        optional INTEGER NOT NULL,
+        default_features INTEGER NOT NULL,
+        features TEXT NOT NULL,
+        target TEXT,
+        kind INTEGER NOT NULL,
+        explicit_name TEXT
+    );
+    CREATE INDEX IF NOT EXISTS dependencies_version_id ON dependencies (version_id);
+    CREATE TABLE IF NOT EXISTS keywords (
+        id INTEGER PRIMARY KEY,
+        keyword TEXT NOT NULL,
+        crates_cnt INTEGER NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS crates_keywords (
+        crate_id INTEGER NOT NULL,
+        keyword_id INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS crates_keywords_crate_id ON crates_keywords (crate_id);
+    CREATE TABLE IF NOT EXISTS categories (
+        id INTEGER PRIMARY KEY,
+        category TEXT NOT NULL,
+        slug TEXT NOT NULL,
+        description TEXT NOT NULL,
+        crates_cnt INTEGER NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS crates_categories (
+        crate_id INTEGER NOT NULL,
+        category_id INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS crates_categories_crate_id ON crates_categories (crate_id);
+    CREATE TABLE IF NOT EXISTS users (
+        id INTEGER PRIMARY KEY,
+        gh_login TEXT NOT NULL,
+        name TEXT,
+        gh_avatar TEXT
+    );
+    CREATE TABLE IF NOT EXISTS version_downloads (
+        version_id INTEGER NOT NULL,
+        date TEXT NOT NULL,
+        downloads INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS version_downloads_version_id ON version_downloads (version_id);
+    CREATE TABLE IF NOT EXISTS teams (
+        id INTEGER PRIMARY KEY,
+        login TEXT NOT NULL,
+        name TEXT,
+        avatar TEXT
+    );
+    CREATE TABLE IF NOT EXISTS crate_owners (
+        crate_id INTEGER NOT NULL,
+        owner_id INTEGER NOT NULL,
+        owner_kind INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS crate_owners_crate_id ON crate_owners (crate_id);
+    CREATE INDEX IF NOT EXISTS dependencies_crate_id ON dependencies (crate_id);
+    CREATE TABLE IF NOT EXISTS crate_downloads (
+        crate_id INTEGER PRIMARY KEY,
+        downloads INTEGER NOT NULL
+    );
+";
+
+const TABLES: &[&str] = &[
+    "crates",
+    "versions",
+    "dependencies",
+    "keywords",
+    "crates_keywords",
+    "categories",
+    "crates_categories",
+    "users",
+    "version_downloads",
+    "teams",
+    "crate_owners",
+    "crate_downloads",
+];
+
+/// Rows imported per table.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub crates: usize,
+    pub versions: usize,
+    pub dependencies: usize,
+    pub keywords: usize,
+    pub categories: usize,
+    pub users: usize,
+    pub version_downloads: usize,
+    pub teams: usize,
+}
+
+/// Imports the official crates.io `db-dump.tar.gz` into the SQLite database at `path`.
+pub fn import_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    dump: P,
+    path: Q,
+) -> RustKataResult<ImportSummary> {
+    let dump = std::fs::File::open(dump).map_err(|err| {
+        log::error!("failed to open database dump: {}", err);
+        RustKataError::Internal
+    })?;
+    let mut connection = Connection::open(path).map_err(|_| RustKataError::Internal)?;
+
+    import(dump, &mut connection)
+}
+
+/// Replaces everything previously imported with the tables found in the gzipped tarball.
+/// The import runs in a single transaction, so readers never see a partial dump.
+pub fn import<R: Read>(dump: R, connection: &mut Connection) -> RustKataResult<ImportSummary> {
+    connection
+        .execute_batch(SCHEMA)
+        .map_err(|_| RustKataError::Internal)?;
+
+    let transaction = connection
+        .transaction()
+        .map_err(|_| RustKataError::Internal)?;
+
+    for table in TABLES {
+        transaction
+            .execute(&format!("DELETE FROM {}", table), [])
+            .map_err(|_| RustKataError::Internal)?;
+    }
+
+    let mut summary = ImportSummary::default();
+
+    let mut archive = tar::Archive::new(GzDecoder::new(dump));
+    for entry in archive.entries().map_err(invalid)? {
+        let entry = entry.map_err(invalid)?;
+
+        // tables live in `<timestamp>/data/<table>.csv`
+        let table = {
+            let path = entry.path().map_err(invalid)?;
+            match (
+                path.parent().and_then(Path::file_name),
+                path.extension(),
+                path.file_stem(),
+            ) {
+                (Some(parent), Some(extension), Some(stem))
+                    if parent == "data" && extension == "csv" =>
+                {
+                    stem.to_string_lossy().into_owned()
+                }
+                _ => continue,
+            }
+        };
+
+        let mut reader = csv::Reader::from_reader(entry);
+
+        match table.as_str() {
+            "crates" => summary.crates = insert(&transaction, &mut reader, insert_crate)?,
+            "versions" => summary.versions = insert(&transaction, &mut reader, insert_version)?,
+            "dependencies" => {
+                summary.dependencies = insert(&transaction, &mut reader, insert_dependency)?
+            }
+            "keywords" => summary.keywords = insert(&transaction, &mut reader, insert_keyword)?,
+            "crates_keywords" => {
+                insert(&transaction, &mut reader, insert_crate_keyword)?;
+            }
+            "categories" => {
+                summary.categories = insert(&transaction, &mut reader, insert_category)?
+            }
+            "crates_categories" => {
+                insert(&transaction, &mut reader, insert_crate_category)?;
+            }
+            "users" => summary.users = insert(&transaction, &mut reader, insert_user)?,
+            "version_downloads" => {
+                summary.version_downloads =
+                    insert(&transaction, &mut reader, insert_version_download)?
+            }
+            "teams" => summary.teams = insert(&transaction, &mut reader, insert_team)?,
+            "crate_owners" => {
+                insert(&transaction, &mut reader, insert_crate_owner)?;
+            }
+            "crate_downloads" => {
+                insert(&transaction, &mut reader, insert_crate_downloads)?;
+            }
+            _ => {}
+        }
+    }
+
+    // current dumps keep the downloads of a crate in `crate_downloads.csv`, which may come
+    // before or after `crates.csv` in the tarball
+    transaction
+        .execute(
+            "UPDATE crates
+             SET downloads = (SELECT downloads FROM crate_downloads
+                              WHERE crate_downloads.crate_id = crates.id)
+             WHERE id IN (SELECT crate_id FROM crate_downloads)",
+            [],
+        )
+        .map_err(|err| {
+            log::error!("failed to import crate downloads: {}", err);
+            RustKataError::Internal
+        })?;
+
+    transaction.commit().map_err(|_| RustKataError::Internal)?;
+
+    Ok(summary)
+}
+
+fn insert<R, T, F>(
+    transaction: &Transaction,
+    reader: &mut csv::Reader<R>,
+    insert_row: F,
+) -> RustKataResult<usize>
+where
+    R: Read,
+    T: for<'de> Deserialize<'de>,
+    F: Fn(&Transaction, T) -> rusqlite::Result<usize>,
+{
+    let mut rows = 0;
+
+    for row in reader.deserialize() {
+        let row = row.map_err(invalid)?;
+        insert_row(transaction, row).map_err(|err| {
+            log::error!("failed to import database dump row: {}", err);
+            RustKataError::Internal
+        })?;
+        rows += 1;
+    }
+
+    Ok(rows)
+}
+
+fn invalid<E: std::fmt::Display>(err: E) -> RustKataError {
+    log::error!("invalid database dump: {}", err);
+    RustKataError::Internal
+}
+
+#[derive(Deserialize)]
+struct CrateRow {
+    id: i64,
+    name: String,
+    description: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    repository: Option<String>,
+    /// Only in older dumps, current ones have `crate_downloads.csv` instead.
+    #[serde(default)]
+    downloads: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+fn insert_crate(transaction: &Transaction, row: CrateRow) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached(
+            "INSERT INTO crates (id, name, description, homepage, documentation, repository,
+                                 downloads, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?
+        .execute(params![
+            row.id,
+            row.name,
+            row.description,
+            row.homepage,
+            row.documentation,
+            row.repository,
+            row.downloads,
+            row.created_at,
+            row.updated_at
+        ])
+}
+
+#[derive(Deserialize)]
+struct VersionRow {
+    id: i64,
+    crate_id: i64,
+    num: String,
+    #[serde(default)]
+    checksum: Option<String>,
+    crate_size: Option<i64>,
+    created_at: String,
+    updated_at: String,
+    downloads: i64,
+    features: String,
+    license: Option<String>,
+    #[serde(default)]
+    links: Option<String>,
+    published_by: Option<i64>,
+    #[serde(default)]
+    rust_version: Option<String>,
+    #[serde(deserialize_with = "boolean")]
+    yanked: bool,
+}
+
+fn insert_version(transaction: &Transaction, row: VersionRow) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached(
+            "INSERT INTO versions (id, crate_id, num, checksum, crate_size, created_at,
+                                   updated_at, downloads, features, license, links,
+                                   published_by, rust_version, yanked)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        )?
+        .execute(params![
+            row.id,
+            row.crate_id,
+            row.num,
+            row.checksum,
+            row.crate_size,
+            row.created_at,
+            row.updated_at,
+            row.downloads,
+            row.features,
+            row.license,
+            row.links,
+            row.published_by,
+            row.rust_version,
+            row.yanked
+        ])
+}
+
+#[derive(Deserialize)]
+struct DependencyRow {
+    id: i64,
+    version_id: i64,
+    crate_id: i64,
+    req: String,
+    #[serde(deserialize_with = "boolean")]
+    optional: bool,
+    #[serde(deserialize_with = "boolean")]
+    default_features: bool,
+    #[serde(deserialize_with = "array")]
+    features: Vec<String>,
+    target: Option<String>,
+    kind: i64,
+    #[serde(default)]
+    explicit_name: Option<String>,
+}
+
+fn insert_dependency(transaction: &Transaction, row: DependencyRow) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached(
+            "INSERT INTO dependencies (id, version_id, crate_id, req, optional,
+                                       default_features, features, target, kind,
+                                       explicit_name)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?
+        .execute(params![
+            row.id,
+            row.version_id,
+            row.crate_id,
+            row.req,
+            row.optional,
+            row.default_features,
+            serde_json::to_string(&row.features).unwrap_or_default(),
+            row.target,
+            row.kind,
+            row.explicit_name
+        ])
+}
+
+#[derive(Deserialize)]
+struct KeywordRow {
+    id: i64,
+    keyword: String,
+    crates_cnt: i64,
+    created_at: String,
+}
+
+fn insert_keyword(transaction: &Transaction, row: KeywordRow) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached(
+            "INSERT INTO keywords (id, keyword, crates_cnt, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?
+        .execute(params![row.id, row.keyword, row.crates_cnt, row.created_at])
+}
+
+#[derive(Deserialize)]
+struct CrateKeywordRow {
+    crate_id: i64,
+    keyword_id: i64,
+}
+
+fn insert_crate_keyword(
+    transaction: &Transaction,
+    row: CrateKeywordRow,
+) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached("INSERT INTO crates_keywords (crate_id, keyword_id) VALUES (?1, ?2)")?
+        .execute(params![row.crate_id, row.keyword_id])
+}
+
+#[derive(Deserialize)]
+struct CategoryRow {
+    id: i64,
+    category: String,
+    slug: String,
+    description: String,
+    crates_cnt: i64,
+    created_at: String,
+}
+
+fn insert_category(transaction: &Transaction, row: CategoryRow) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached(
+            "INSERT INTO categories (id, category, slug, description, crates_cnt, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?
+        .execute(params![
+            row.id,
+            row.category,
+            row.slug,
+            row.description,
+            row.crates_cnt,
+            row.created_at
+        ])
+}
+
+#[derive(Deserialize)]
+struct CrateCategoryRow {
+    crate_id: i64,
+    category_id: i64,
+}
+
+fn insert_crate_category(
+    transaction: &Transaction,
+    row: CrateCategoryRow,
+) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached("INSERT INTO crates_categories (crate_id, category_id) VALUES (?1, ?2)")?
+        .execute(params![row.crate_id, row.category_id])
+}
+
+#[derive(Deserialize)]
+struct UserRow {
+    id: i64,
+    gh_login: String,
+    name: Option<String>,
+    gh_avatar: Option<String>,
+}
+
+fn insert_user(transaction: &Transaction, row: UserRow) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached(
+            "INSERT INTO users (id, gh_login, name, gh_avatar) VALUES (?1, ?2, ?3, ?4)",
+        )?
+        .execute(params![row.id, row.gh_login, row.name, row.gh_avatar])
+}
+
+#[derive(Deserialize)]
+struct VersionDownloadRow {
+    version_id: i64,
+    date: String,
+    downloads: i64,
+}
+
+fn insert_version_download(
+    transaction: &Transaction,
+    row: VersionDownloadRow,
+) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached(
+            "INSERT INTO version_downloads (version_id, date, downloads) VALUES (?1, ?2, ?3)",
+        )?
+        .execute(params![row.version_id, row.date, row.downloads])
+}
+
+#[derive(Deserialize)]
+struct TeamRow {
+    id: i64,
+    login: String,
+    name: Option<String>,
+    avatar: Option<String>,
+}
+
+fn insert_team(transaction: &Transaction, row: TeamRow) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached("INSERT INTO teams (id, login, name, avatar) VALUES (?1, ?2, ?3, ?4)")?
+        .execute(params![row.id, row.login, row.name, row.avatar])
+}
+
+/// `owner_kind` is 0 for a user and 1 for a team.
+#[derive(Deserialize)]
+struct CrateOwnerRow {
+    crate_id: i64,
+    owner_id: i64,
+    owner_kind: i64,
+}
+
+fn insert_crate_owner(transaction: &Transaction, row: CrateOwnerRow) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached(
+            "INSERT INTO crate_owners (crate_id, owner_id, owner_kind) VALUES (?1, ?2, ?3)",
+        )?
+        .execute(params![row.crate_id, row.owner_id, row.owner_kind])
+}
+
+#[derive(Deserialize)]
+struct CrateDownloadsRow {
+    crate_id: i64,
+    downloads: i64,
+}
+
+fn insert_crate_downloads(
+    transaction: &Transaction,
+    row: CrateDownloadsRow,
+) -> rusqlite::Result<usize> {
+    transaction
+        .prepare_cached("INSERT INTO crate_downloads (crate_id, downloads) VALUES (?1, ?2)")?
+        .execute(params![row.crate_id, row.downloads])
+}
+
+/// PostgreSQL writes booleans as `t` and `f`.
+fn boolean<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    match String::deserialize(deserializer)?.as_str() {
+        "t" | "true" => Ok(true),
+        "f" | "false" => Ok(false),
+        other => Err(serde::de::Error::custom(format!(
+            "invalid boolean {}",
+            other
+        ))),
+    }
+}
+
+/// PostgreSQL writes text arrays as `{a,b,"c d"}`.
+fn array<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    let value = value.trim_start_matches('{').trim_end_matches('}');
+
+    Ok(value
+        .split(',')
+        .filter(|item| !item.is_empty())
+        .map(|item| item.trim_matches('"').to_owned())
+        .collect())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    #[test]
+    fn test_import() {
+        let mut connection = Connection::open_in_memory().unwrap();
+
+        let summary = import(&dump()[..], &mut connection).unwrap();
+
+        assert_eq!(
+            summary,
+            ImportSummary {
+                crates: 4,
+                versions: 6,
+                dependencies: 7,
+                keywords: 2,
+                categories: 2,
+                users: 1,
+                version_downloads: 5,
+                teams: 1,
+            }
+        );
+
+        let (features, optional): (String, bool) = connection
+            .query_row(
+                "SELECT features, optional FROM dependencies WHERE id = 112",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(features, "[\"derive\",\"rc\"]");
+        assert!(optional);
+    }
+
+    #[test]
+    fn test_import_replaces_previous_import() {
+        let mut connection = Connection::open_in_memory().unwrap();
+
+        import(&dump()[..], &mut connection).unwrap();
+        import(&dump()[..], &mut connection).unwrap();
+
+        let crates: i64 = connection
+            .query_row("SELECT COUNT(*) FROM crates", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(crates, 4);
+    }
+
+    #[test]
+    fn test_import_crate_downloads() {
+        let mut connection = Connection::open_in_memory().unwrap();
+
+        let summary = import(&pack("2024-06-01-020036")[..], &mut connection).unwrap();
+        assert_eq!(summary.crates, 4);
+
+        let downloads: i64 = connection
+            .query_row(
+                "SELECT downloads FROM crates WHERE name = 'rand'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(downloads, 60437193);
+    }
+
+    #[test]
+    fn test_array() {
+        let parse = |value: &str| {
+            array(serde::de::value::StrDeserializer::<serde::de::value::Error>::new(value)).unwrap()
+        };
+
+        assert_eq!(parse("{}"), Vec::<String>::new());
+        assert_eq!(parse("{derive}"), vec!["derive"]);
+        assert_eq!(parse("{derive,rc}"), vec!["derive", "rc"]);
+    }
+
+    /// The synthetic dump in `tests/fixtures/db-dump`, packed like the official one.
+    pub(crate) fn dump() -> Vec<u8> {
+        pack("2021-01-15-020004")
+    }
+
+    fn pack(timestamp: &str) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+        builder
+            .append_dir_all(timestamp, format!("./tests/fixtures/db-dump/{}", timestamp))
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+}
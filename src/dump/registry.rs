@@ -0,0 +1,961 @@
+use crate::dump::importer::SCHEMA;
+use crate::errors::{RustKataError, RustKataResult};
+use crate::index::models::{crate_links, max_version, version_links};
+use crate::interfaces::crate_registry::{
+    get_categories, get_category, get_crate, get_crate_dependencies, get_crate_downloads,
+    get_crate_owners, get_keyword, get_keywords, get_reverse_dependencies, CrateRegistry,
+    MetaResponse, Page,
+};
+use actix_web::web;
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Registry served from an imported crates.io database dump, without any network access.
+/// Besides crates and their dependencies it answers for owners, downloads, reverse
+/// dependencies, keywords and categories, all of which the dump holds. Queries run on the
+/// blocking thread pool, never on the workers.
+pub struct DumpRegistry {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl DumpRegistry {
+    pub fn open<P: AsRef<Path>>(path: P) -> RustKataResult<Self> {
+        DumpRegistry::initialize(Connection::open(path).map_err(|_| RustKataError::Internal)?)
+    }
+
+    fn initialize(connection: Connection) -> RustKataResult<Self> {
+        connection
+            .execute_batch(SCHEMA)
+            .map_err(|_| RustKataError::Internal)?;
+
+        Ok(DumpRegistry {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Runs `query` against the dump, `what` naming what it reads in logs.
+    async fn query<T, F>(&self, what: &str, query: F) -> RustKataResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+
+        web::block(move || query(&connection.lock().unwrap()))
+            .await
+            .map_err(|err| {
+                log::warn!("failed to read {} from database dump: {}", what, err);
+                RustKataError::Internal
+            })
+    }
+}
+
+fn crate_(
+    connection: &Connection,
+    crate_name: &str,
+) -> rusqlite::Result<Option<get_crate::Response>> {
+    let crate_ = connection
+        .query_row(
+            "SELECT id, name, description, homepage, documentation, repository, downloads,
+                        created_at, updated_at
+                 FROM crates WHERE name = ?1 COLLATE NOCASE",
+            params![crate_name],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    get_crate::CrateResponse {
+                        id: row.get(1)?,
+                        name: row.get(1)?,
+                        updated_at: timestamp(&row.get::<_, String>(8)?),
+                        versions: vec![],
+                        keywords: vec![],
+                        categories: vec![],
+                        badges: vec![],
+                        created_at: timestamp(&row.get::<_, String>(7)?),
+                        downloads: row.get(6)?,
+                        recent_downloads: 0,
+                        max_version: String::new(),
+                        newest_version: String::new(),
+                        description: row.get(2)?,
+                        homepage: row.get(3)?,
+                        documentation: row.get(4)?,
+                        repository: row.get(5)?,
+                        links: crate_links(&row.get::<_, String>(1)?),
+                        exact_match: false,
+                        extra: Default::default(),
+                    },
+                ))
+            },
+        )
+        .optional()?;
+
+    let (id, mut crate_) = match crate_ {
+        Some(crate_) => crate_,
+        None => return Ok(None),
+    };
+
+    let versions = connection
+        .prepare(&format!(
+            "SELECT {} FROM versions {} WHERE versions.crate_id = ?1 ORDER BY versions.id DESC",
+            VERSION_COLUMNS, PUBLISHED_BY
+        ))?
+        .query_map(params![id], |row| version(row, &crate_.name))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let keywords = connection
+        .prepare(&format!(
+            "SELECT {}
+                 FROM keywords
+                 JOIN crates_keywords ON crates_keywords.keyword_id = keywords.id
+                 WHERE crates_keywords.crate_id = ?1
+                 ORDER BY keywords.keyword",
+            KEYWORD_COLUMNS
+        ))?
+        .query_map(params![id], keyword)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let categories = connection
+        .prepare(&format!(
+            "SELECT {}
+                 FROM categories
+                 JOIN crates_categories ON crates_categories.category_id = categories.id
+                 WHERE crates_categories.crate_id = ?1
+                 ORDER BY categories.slug",
+            CATEGORY_COLUMNS
+        ))?
+        .query_map(params![id], category)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    // the dump only carries the last 90 days of downloads per version
+    crate_.recent_downloads = connection.query_row(
+        "SELECT COALESCE(SUM(version_downloads.downloads), 0)
+             FROM version_downloads
+             JOIN versions ON versions.id = version_downloads.version_id
+             WHERE versions.crate_id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    crate_.versions = versions.iter().map(|version| version.id).collect();
+    crate_.keywords = keywords.iter().map(|keyword| keyword.id.clone()).collect();
+    crate_.categories = categories
+        .iter()
+        .map(|category| category.id.clone())
+        .collect();
+    crate_.max_version = max_version(
+        versions
+            .iter()
+            .map(|version| (version.num.as_str(), version.yanked)),
+    )
+    .to_owned();
+    crate_.newest_version = versions
+        .first()
+        .map(|version| version.num.clone())
+        .unwrap_or_default();
+
+    Ok(Some(get_crate::Response {
+        crate_,
+        versions,
+        keywords,
+        categories,
+        served_by: None,
+        upstream: Default::default(),
+        extra: Default::default(),
+    }))
+}
+
+fn dependencies(
+    connection: &Connection,
+    crate_name: &str,
+    crate_version: &str,
+) -> rusqlite::Result<Option<get_crate_dependencies::Response>> {
+    let version_id: Option<i64> = connection
+        .query_row(
+            "SELECT versions.id
+                 FROM versions
+                 JOIN crates ON crates.id = versions.crate_id
+                 WHERE crates.name = ?1 COLLATE NOCASE AND versions.num = ?2",
+            params![crate_name, crate_version],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let version_id = match version_id {
+        Some(version_id) => version_id,
+        None => return Ok(None),
+    };
+
+    let dependencies = connection
+        .prepare(&format!(
+            "SELECT {}
+                 FROM dependencies
+                 JOIN crates ON crates.id = dependencies.crate_id
+                 WHERE dependencies.version_id = ?1
+                 ORDER BY dependencies.id",
+            DEPENDENCY_COLUMNS
+        ))?
+        .query_map(params![version_id], dependency)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(Some(get_crate_dependencies::Response {
+        dependencies,
+        served_by: None,
+        upstream: Default::default(),
+        extra: Default::default(),
+    }))
+}
+
+fn crate_id(connection: &Connection, crate_name: &str) -> rusqlite::Result<Option<i64>> {
+    connection
+        .query_row(
+            "SELECT id FROM crates WHERE name = ?1 COLLATE NOCASE",
+            params![crate_name],
+            |row| row.get(0),
+        )
+        .optional()
+}
+
+fn owners(
+    connection: &Connection,
+    crate_name: &str,
+) -> rusqlite::Result<Option<get_crate_owners::Response>> {
+    let crate_id = match crate_id(connection, crate_name)? {
+        Some(crate_id) => crate_id,
+        None => return Ok(None),
+    };
+
+    let users = connection
+        .prepare(
+            "SELECT 'user', users.id, users.gh_login, users.name, users.gh_avatar
+                 FROM crate_owners
+                 JOIN users ON users.id = crate_owners.owner_id
+                 WHERE crate_owners.crate_id = ?1 AND crate_owners.owner_kind = 0
+             UNION ALL
+             SELECT 'team', teams.id, teams.login, teams.name, teams.avatar
+                 FROM crate_owners
+                 JOIN teams ON teams.id = crate_owners.owner_id
+                 WHERE crate_owners.crate_id = ?1 AND crate_owners.owner_kind = 1
+             ORDER BY 1 DESC, 3",
+        )?
+        .query_map(params![crate_id], |row| {
+            let kind: String = row.get(0)?;
+            let login: String = row.get(2)?;
+
+            // teams log in as `github:<org>:<team>` and link to their organization
+            let url = match kind.as_str() {
+                "team" => login
+                    .split(':')
+                    .nth(1)
+                    .map(|org| format!("https://github.com/{}", org)),
+                _ => Some(format!("https://github.com/{}", login)),
+            };
+
+            Ok(get_crate_owners::OwnerResponse {
+                id: row.get(1)?,
+                login,
+                kind,
+                name: row.get(3)?,
+                avatar: row.get(4)?,
+                url,
+                extra: Default::default(),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(Some(get_crate_owners::Response {
+        users,
+        upstream: Default::default(),
+        extra: Default::default(),
+    }))
+}
+
+/// Daily downloads of every version of the crate, or of `crate_version` only.
+fn downloads(
+    connection: &Connection,
+    crate_name: &str,
+    crate_version: Option<&str>,
+) -> rusqlite::Result<Option<get_crate_downloads::Response>> {
+    let versions = "FROM versions
+             JOIN crates ON crates.id = versions.crate_id
+             WHERE crates.name = ?1 COLLATE NOCASE AND (?2 IS NULL OR versions.num = ?2)";
+
+    let exists: bool = connection.query_row(
+        &format!("SELECT EXISTS (SELECT 1 {})", versions),
+        params![crate_name, crate_version],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(None);
+    }
+
+    let version_downloads = connection
+        .prepare(&format!(
+            "SELECT version_downloads.version_id, version_downloads.downloads,
+                    version_downloads.date
+                 FROM version_downloads
+                 JOIN (SELECT versions.id {}) AS versions
+                     ON versions.id = version_downloads.version_id
+                 ORDER BY version_downloads.date, version_downloads.version_id",
+            versions
+        ))?
+        .query_map(params![crate_name, crate_version], |row| {
+            Ok(get_crate_downloads::VersionDownloadsResponse {
+                version: row.get(0)?,
+                downloads: row.get(1)?,
+                date: row.get(2)?,
+                extra: Default::default(),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(Some(get_crate_downloads::Response {
+        version_downloads,
+        // every version is listed, none is left over for `extra_downloads`
+        meta: crate_version.map_or_else(|| Some(Default::default()), |_| None),
+        upstream: Default::default(),
+        extra: Default::default(),
+    }))
+}
+
+/// Dependencies on the crate declared by the latest non-yanked version of each dependent,
+/// most downloaded dependents first.
+fn reverse_dependencies(
+    connection: &Connection,
+    crate_name: &str,
+    page: Page,
+) -> rusqlite::Result<Option<get_reverse_dependencies::Response>> {
+    let crate_id = match crate_id(connection, crate_name)? {
+        Some(crate_id) => crate_id,
+        None => return Ok(None),
+    };
+
+    let dependents = "FROM dependencies
+             JOIN crates ON crates.id = dependencies.crate_id
+             JOIN versions ON versions.id = dependencies.version_id
+             JOIN crates AS dependents ON dependents.id = versions.crate_id
+             WHERE dependencies.crate_id = ?1
+               AND versions.id = (SELECT MAX(latest.id)
+                                      FROM versions AS latest
+                                      WHERE latest.crate_id = versions.crate_id
+                                        AND NOT latest.yanked)";
+
+    let total = connection.query_row(
+        &format!("SELECT COUNT(*) {}", dependents),
+        params![crate_id],
+        |row| row.get(0),
+    )?;
+
+    let (limit, offset) = limit(page);
+    let dependencies = connection
+        .prepare(&format!(
+            "SELECT {} {}
+                 ORDER BY dependents.downloads DESC, dependents.id, dependencies.id
+                 LIMIT ?2 OFFSET ?3",
+            DEPENDENCY_COLUMNS, dependents
+        ))?
+        .query_map(params![crate_id, limit, offset], dependency)?
+        .collect::<rusqlite::Result<Vec<get_crate_dependencies::DependencyResponse>>>()?;
+
+    let mut version_ids: Vec<i64> = dependencies
+        .iter()
+        .map(|dependency| dependency.version_id)
+        .collect();
+    version_ids.dedup();
+
+    let mut statement = connection.prepare(&format!(
+        "SELECT {}, crates.name
+             FROM versions
+             JOIN crates ON crates.id = versions.crate_id
+             {}
+             WHERE versions.id = ?1",
+        VERSION_COLUMNS, PUBLISHED_BY
+    ))?;
+    let versions = version_ids
+        .into_iter()
+        .map(|version_id| {
+            statement.query_row(params![version_id], |row| {
+                version(row, &row.get::<_, String>(VERSION_COLUMNS_LEN)?)
+            })
+        })
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(Some(get_reverse_dependencies::Response {
+        dependencies,
+        versions,
+        meta: MetaResponse {
+            total,
+            extra: Default::default(),
+        },
+        upstream: Default::default(),
+        extra: Default::default(),
+    }))
+}
+
+fn keywords(connection: &Connection, page: Page) -> rusqlite::Result<get_keywords::Response> {
+    let (limit, offset) = limit(page);
+
+    Ok(get_keywords::Response {
+        keywords: connection
+            .prepare(&format!(
+                "SELECT {} FROM keywords ORDER BY keywords.keyword LIMIT ?1 OFFSET ?2",
+                KEYWORD_COLUMNS
+            ))?
+            .query_map(params![limit, offset], keyword)?
+            .collect::<rusqlite::Result<Vec<_>>>()?,
+        meta: MetaResponse {
+            total: connection.query_row("SELECT COUNT(*) FROM keywords", [], |row| row.get(0))?,
+            extra: Default::default(),
+        },
+        upstream: Default::default(),
+        extra: Default::default(),
+    })
+}
+
+fn categories(connection: &Connection, page: Page) -> rusqlite::Result<get_categories::Response> {
+    let (limit, offset) = limit(page);
+
+    Ok(get_categories::Response {
+        categories: connection
+            .prepare(&format!(
+                "SELECT {} FROM categories ORDER BY categories.category LIMIT ?1 OFFSET ?2",
+                CATEGORY_COLUMNS
+            ))?
+            .query_map(params![limit, offset], category)?
+            .collect::<rusqlite::Result<Vec<_>>>()?,
+        meta: MetaResponse {
+            total: connection.query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0))?,
+            extra: Default::default(),
+        },
+        upstream: Default::default(),
+        extra: Default::default(),
+    })
+}
+
+#[async_trait]
+impl CrateRegistry for DumpRegistry {
+    async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response> {
+        let name = crate_name.to_owned();
+
+        self.query(crate_name, move |connection| crate_(connection, &name))
+            .await?
+            .ok_or(RustKataError::NotFound)
+    }
+
+    async fn get_crate_dependencies(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_dependencies::Response> {
+        let (name, version) = (crate_name.to_owned(), crate_version.to_owned());
+
+        self.query(
+            &format!("{} {}", crate_name, crate_version),
+            move |connection| dependencies(connection, &name, &version),
+        )
+        .await?
+        .ok_or(RustKataError::NotFound)
+    }
+
+    async fn get_crate_owners(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_owners::Response> {
+        let name = crate_name.to_owned();
+
+        self.query(crate_name, move |connection| owners(connection, &name))
+            .await?
+            .ok_or(RustKataError::NotFound)
+    }
+
+    async fn get_crate_downloads(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_downloads::Response> {
+        let name = crate_name.to_owned();
+
+        self.query(crate_name, move |connection| {
+            downloads(connection, &name, None)
+        })
+        .await?
+        .ok_or(RustKataError::NotFound)
+    }
+
+    async fn get_version_downloads(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_downloads::Response> {
+        let (name, version) = (crate_name.to_owned(), crate_version.to_owned());
+
+        self.query(
+            &format!("{} {}", crate_name, crate_version),
+            move |connection| downloads(connection, &name, Some(&version)),
+        )
+        .await?
+        .ok_or(RustKataError::NotFound)
+    }
+
+    async fn get_reverse_dependencies(
+        &self,
+        crate_name: &str,
+        page: &Page,
+    ) -> RustKataResult<get_reverse_dependencies::Response> {
+        let (name, page) = (crate_name.to_owned(), *page);
+
+        self.query(crate_name, move |connection| {
+            reverse_dependencies(connection, &name, page)
+        })
+        .await?
+        .ok_or(RustKataError::NotFound)
+    }
+
+    async fn get_keywords(&self, page: &Page) -> RustKataResult<get_keywords::Response> {
+        let page = *page;
+
+        self.query("keywords", move |connection| keywords(connection, page))
+            .await
+    }
+
+    async fn get_keyword(&self, keyword_name: &str) -> RustKataResult<get_keyword::Response> {
+        let name = keyword_name.to_owned();
+
+        self.query(keyword_name, move |connection| {
+            connection
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM keywords WHERE keywords.keyword = ?1 COLLATE NOCASE",
+                        KEYWORD_COLUMNS
+                    ),
+                    params![name],
+                    keyword,
+                )
+                .optional()
+        })
+        .await?
+        .map(|keyword| get_keyword::Response {
+            keyword,
+            upstream: Default::default(),
+            extra: Default::default(),
+        })
+        .ok_or(RustKataError::NotFound)
+    }
+
+    async fn get_categories(&self, page: &Page) -> RustKataResult<get_categories::Response> {
+        let page = *page;
+
+        self.query("categories", move |connection| categories(connection, page))
+            .await
+    }
+
+    async fn get_category(&self, slug: &str) -> RustKataResult<get_category::Response> {
+        let slug_ = slug.to_owned();
+
+        self.query(slug, move |connection| {
+            connection
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM categories WHERE categories.slug = ?1",
+                        CATEGORY_COLUMNS
+                    ),
+                    params![slug_],
+                    category,
+                )
+                .optional()
+        })
+        .await?
+        .map(|category| get_category::Response {
+            category,
+            upstream: Default::default(),
+            extra: Default::default(),
+        })
+        .ok_or(RustKataError::NotFound)
+    }
+}
+
+/// Columns `version` reads, the publisher's coming from joining `PUBLISHED_BY`.
+const VERSION_COLUMNS: &str = "versions.id, versions.num, versions.checksum, versions.crate_size,
+    versions.created_at, versions.updated_at, versions.downloads, versions.features,
+    versions.license, versions.rust_version, versions.yanked,
+    users.id, users.gh_login, users.name, users.gh_avatar";
+const VERSION_COLUMNS_LEN: usize = 15;
+const PUBLISHED_BY: &str = "LEFT JOIN users ON users.id = versions.published_by";
+
+fn version(row: &Row, crate_name: &str) -> rusqlite::Result<get_crate::VersionResponse> {
+    let num: String = row.get(1)?;
+    let features: String = row.get(7)?;
+
+    let published_by = match row.get::<_, Option<String>>(12)? {
+        Some(login) => Some(get_crate::UserResponse {
+            id: row.get(11)?,
+            url: format!("https://github.com/{}", login),
+            login,
+            name: row.get(13)?,
+            avatar: row.get(14)?,
+            extra: Default::default(),
+        }),
+        None => None,
+    };
+
+    Ok(get_crate::VersionResponse {
+        id: row.get(0)?,
+        crate_: crate_name.to_owned(),
+        dl_path: format!("/api/v1/crates/{}/{}/download", crate_name, num),
+        readme_path: format!("/api/v1/crates/{}/{}/readme", crate_name, num),
+        updated_at: timestamp(&row.get::<_, String>(5)?),
+        created_at: timestamp(&row.get::<_, String>(4)?),
+        downloads: row.get(6)?,
+        features: serde_json::from_str(&features).unwrap_or_default(),
+        yanked: row.get(10)?,
+        license: row.get(8)?,
+        links: version_links(crate_name, &num),
+        crate_size: row.get(3)?,
+        published_by,
+        audit_actions: vec![],
+        checksum: row.get(2)?,
+        rust_version: row.get(9)?,
+        num,
+        extra: Default::default(),
+    })
+}
+
+/// Columns `dependency` reads, `crates` being the crate depended on.
+const DEPENDENCY_COLUMNS: &str = "dependencies.id, dependencies.version_id, crates.name,
+    dependencies.req, dependencies.optional, dependencies.default_features,
+    dependencies.features, dependencies.target, dependencies.kind";
+
+fn dependency(row: &Row) -> rusqlite::Result<get_crate_dependencies::DependencyResponse> {
+    let features: String = row.get(6)?;
+
+    Ok(get_crate_dependencies::DependencyResponse {
+        id: row.get(0)?,
+        version_id: row.get(1)?,
+        crate_id: row.get(2)?,
+        req: row.get(3)?,
+        optional: row.get(4)?,
+        default_features: row.get(5)?,
+        features: serde_json::from_str(&features).ok(),
+        target: row.get(7)?,
+        kind: match row.get::<_, i64>(8)? {
+            1 => "build",
+            2 => "dev",
+            _ => "normal",
+        }
+        .to_owned(),
+        downloads: 0,
+        registry: None,
+        extra: Default::default(),
+    })
+}
+
+const KEYWORD_COLUMNS: &str = "keywords.keyword, keywords.created_at, keywords.crates_cnt";
+
+fn keyword(row: &Row) -> rusqlite::Result<get_crate::KeywordResponse> {
+    Ok(get_crate::KeywordResponse {
+        id: row.get(0)?,
+        keyword: row.get(0)?,
+        created_at: timestamp(&row.get::<_, String>(1)?),
+        crates_cnt: row.get(2)?,
+        extra: Default::default(),
+    })
+}
+
+const CATEGORY_COLUMNS: &str = "categories.slug, categories.category, categories.description,
+    categories.created_at, categories.crates_cnt";
+
+fn category(row: &Row) -> rusqlite::Result<get_crate::CategoryResponse> {
+    Ok(get_crate::CategoryResponse {
+        id: row.get(0)?,
+        category: row.get(1)?,
+        slug: row.get(0)?,
+        description: row.get(2)?,
+        created_at: timestamp(&row.get::<_, String>(3)?),
+        crates_cnt: row.get(4)?,
+        extra: Default::default(),
+    })
+}
+
+/// `LIMIT` and `OFFSET` of the page.
+fn limit(page: Page) -> (i64, i64) {
+    (
+        page.per_page as i64,
+        (page.page.saturating_sub(1) * page.per_page) as i64,
+    )
+}
+
+/// The dump writes timestamps the way PostgreSQL prints them, while the api uses RFC 3339.
+fn timestamp(value: &str) -> String {
+    if value.contains('T') {
+        return value.to_owned();
+    }
+
+    format!("{}+00:00", value.replacen(' ', "T", 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump::importer::{import, tests::dump};
+
+    #[actix_rt::test]
+    async fn test_get_crate() {
+        let response = registry().get_crate("Rand").await.unwrap();
+
+        assert_eq!(response.crate_.name, "rand");
+        assert_eq!(response.crate_.downloads, 60436193);
+        assert_eq!(response.crate_.recent_downloads, 19279);
+        assert_eq!(response.crate_.max_version, "0.8.2");
+        assert_eq!(response.crate_.newest_version, "0.8.2");
+        assert_eq!(response.crate_.versions, vec![11, 10]);
+        assert_eq!(response.crate_.keywords, vec!["random", "rng"]);
+        assert_eq!(response.crate_.categories, vec!["algorithms", "no-std"]);
+        assert_eq!(
+            response.crate_.updated_at,
+            "2021-01-13T09:55:20.922296+00:00"
+        );
+
+        assert_eq!(response.versions[0].num, "0.8.2");
+        assert_eq!(response.versions[0].crate_size, Some(84288));
+        assert_eq!(
+            response.versions[0].features["default"],
+            vec!["std", "std_rng"]
+        );
+        assert_eq!(
+            response.versions[0].published_by.as_ref().unwrap().login,
+            "dhardy"
+        );
+
+        assert_eq!(response.keywords[1].crates_cnt, 58);
+        assert_eq!(response.categories[1].category, "No standard library");
+    }
+
+    #[actix_rt::test]
+    async fn test_get_crate_max_version_skips_yanked() {
+        let response = registry().get_crate("rand_core").await.unwrap();
+
+        assert_eq!(response.crate_.max_version, "0.6.1");
+        assert!(response.versions[1].yanked);
+        assert!(response.versions[1].published_by.is_some());
+    }
+
+    #[actix_rt::test]
+    async fn test_get_crate_dependencies() {
+        let response = registry()
+            .get_crate_dependencies("rand", "0.8.2")
+            .await
+            .unwrap();
+
+        assert_eq!(response.dependencies.len(), 4);
+        assert_eq!(response.dependencies[0].crate_id, "rand_core");
+        assert_eq!(response.dependencies[1].crate_id, "libc");
+        assert_eq!(
+            response.dependencies[1].target,
+            Some("cfg(unix)".to_owned())
+        );
+        assert!(!response.dependencies[1].default_features);
+        assert_eq!(
+            response.dependencies[2].features,
+            Some(vec!["derive".to_owned(), "rc".to_owned()])
+        );
+        assert_eq!(response.dependencies[3].kind, "dev");
+    }
+
+    #[actix_rt::test]
+    async fn test_not_found() {
+        let registry = registry();
+
+        assert!(matches!(
+            registry.get_crate("nonexistent").await,
+            Err(RustKataError::NotFound)
+        ));
+        assert!(matches!(
+            registry.get_crate_dependencies("rand", "0.7.3").await,
+            Err(RustKataError::NotFound)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_get_crate_owners() {
+        let response = registry().get_crate_owners("rand").await.unwrap();
+
+        let owners: Vec<_> = response
+            .users
+            .iter()
+            .map(|owner| {
+                (
+                    owner.kind.as_str(),
+                    owner.login.as_str(),
+                    owner.url.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            owners,
+            vec![
+                ("user", "dhardy", Some("https://github.com/dhardy")),
+                (
+                    "team",
+                    "github:rust-random:maintainers",
+                    Some("https://github.com/rust-random")
+                ),
+            ]
+        );
+        assert!(registry()
+            .get_crate_owners("serde")
+            .await
+            .unwrap()
+            .users
+            .is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_get_crate_downloads() {
+        let registry = registry();
+
+        let response = registry.get_crate_downloads("rand").await.unwrap();
+
+        let downloads: Vec<_> = response
+            .version_downloads
+            .iter()
+            .map(|downloads| {
+                (
+                    downloads.version,
+                    downloads.date.as_str(),
+                    downloads.downloads,
+                )
+            })
+            .collect();
+        assert_eq!(
+            downloads,
+            vec![
+                (10, "2021-01-13", 1200),
+                (11, "2021-01-13", 7301),
+                (10, "2021-01-14", 1512),
+                (11, "2021-01-14", 9266),
+            ]
+        );
+        assert!(response.meta.is_some());
+
+        let response = registry
+            .get_version_downloads("rand", "0.8.2")
+            .await
+            .unwrap();
+
+        assert_eq!(response.version_downloads.len(), 2);
+        assert!(response.meta.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_get_reverse_dependencies() {
+        let registry = registry();
+
+        let response = registry
+            .get_reverse_dependencies("serde", &Page::default())
+            .await
+            .unwrap();
+
+        assert_eq!(response.meta.total, 2);
+        let dependencies: Vec<_> = response
+            .dependencies
+            .iter()
+            .map(|dependency| {
+                (
+                    dependency.id,
+                    dependency.crate_id.as_str(),
+                    dependency.version_id,
+                )
+            })
+            .collect();
+        assert_eq!(dependencies, vec![(112, "serde", 11), (113, "serde", 11)]);
+        assert_eq!(response.versions.len(), 1);
+        assert_eq!(response.versions[0].crate_, "rand");
+        assert_eq!(response.versions[0].num, "0.8.2");
+        assert_eq!(
+            response.versions[0].published_by.as_ref().unwrap().login,
+            "dhardy"
+        );
+
+        let response = registry
+            .get_reverse_dependencies(
+                "serde",
+                &Page {
+                    page: 2,
+                    per_page: 1,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.meta.total, 2);
+        assert_eq!(response.dependencies[0].id, 113);
+    }
+
+    #[actix_rt::test]
+    async fn test_keywords_and_categories() {
+        let registry = registry();
+
+        let response = registry
+            .get_keywords(&Page {
+                page: 1,
+                per_page: 1,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.meta.total, 2);
+        assert_eq!(response.keywords.len(), 1);
+        assert_eq!(response.keywords[0].keyword, "random");
+
+        let response = registry.get_keyword("rng").await.unwrap();
+
+        assert_eq!(response.keyword.crates_cnt, 58);
+
+        let response = registry.get_categories(&Page::default()).await.unwrap();
+
+        assert_eq!(response.meta.total, 2);
+        let categories: Vec<_> = response
+            .categories
+            .iter()
+            .map(|category| category.slug.as_str())
+            .collect();
+        assert_eq!(categories, vec!["algorithms", "no-std"]);
+
+        let response = registry.get_category("no-std").await.unwrap();
+
+        assert_eq!(response.category.category, "No standard library");
+    }
+
+    #[actix_rt::test]
+    async fn test_read_api_not_found() {
+        let registry = registry();
+
+        assert!(matches!(
+            registry.get_crate_owners("nonexistent").await,
+            Err(RustKataError::NotFound)
+        ));
+        assert!(matches!(
+            registry.get_version_downloads("rand", "0.7.3").await,
+            Err(RustKataError::NotFound)
+        ));
+        assert!(matches!(
+            registry
+                .get_reverse_dependencies("nonexistent", &Page::default())
+                .await,
+            Err(RustKataError::NotFound)
+        ));
+        assert!(matches!(
+            registry.get_keyword("nonexistent").await,
+            Err(RustKataError::NotFound)
+        ));
+        assert!(matches!(
+            registry.get_category("nonexistent").await,
+            Err(RustKataError::NotFound)
+        ));
+    }
+
+    fn registry() -> DumpRegistry {
+        let mut connection = Connection::open_in_memory().unwrap();
+        import(&dump()[..], &mut connection).unwrap();
+        DumpRegistry::initialize(connection).unwrap()
+    }
+}
@@ -1,4 +1,202 @@
-#[derive(Debug)]
-pub struct RustKataError {}
+use crate::interfaces::http;
+use crate::observability::metrics;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub enum RustKataError {
+    /// The registry does not know the crate or version.
+    NotFound,
+    /// The upstream answered with a status that is neither a success nor a not found, and
+    /// possibly with how long to wait before asking again.
+    UpstreamStatus {
+        status: u16,
+        retry_after: Option<Duration>,
+    },
+    /// The upstream could not be reached, or the connection broke mid-response.
+    Transport,
+    /// The upstream answered with a body that does not match the expected model.
+    Deserialization,
+    /// A downloaded artifact does not match the checksum the registry published for it.
+    Integrity,
+    /// The upstream did not answer within the request timeout.
+    Timeout,
+    /// The client-side rate limit could not let the request through in time.
+    RateLimited { retry_after: Duration },
+    /// The upstream is failing and the circuit breaker stopped calling it for a while.
+    Unavailable,
+    /// The registry backend has no such data, e.g. owners in an index.
+    Unsupported,
+    /// The request itself is malformed, e.g. an invalid crate name.
+    InvalidInput { description: String },
+    /// Something failed on our side, such as local storage or configuration.
+    Internal,
+}
+
+impl RustKataError {
+    /// Short, stable name of the variant, used as problem `name` and metric label.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RustKataError::NotFound => "not_found",
+            RustKataError::UpstreamStatus { .. } => "upstream_status",
+            RustKataError::Transport => "transport",
+            RustKataError::Deserialization => "deserialization",
+            RustKataError::Integrity => "integrity",
+            RustKataError::Timeout => "timeout",
+            RustKataError::RateLimited { .. } => "rate_limited",
+            RustKataError::Unavailable => "unavailable",
+            RustKataError::Unsupported => "unsupported",
+            RustKataError::InvalidInput { .. } => "invalid_input",
+            RustKataError::Internal => "internal",
+        }
+    }
+}
+
+impl fmt::Display for RustKataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RustKataError::NotFound => write!(f, "not found"),
+            RustKataError::UpstreamStatus { status, .. } => {
+                write!(f, "upstream responded with status {}", status)
+            }
+            RustKataError::Transport => write!(f, "upstream could not be reached"),
+            RustKataError::Deserialization => write!(f, "upstream response could not be read"),
+            RustKataError::Integrity => write!(f, "upstream artifact failed verification"),
+            RustKataError::Timeout => write!(f, "upstream timed out"),
+            RustKataError::RateLimited { retry_after } => write!(
+                f,
+                "rate limited, retry after {}s",
+                retry_after.as_secs_f64().ceil()
+            ),
+            RustKataError::Unavailable => write!(f, "upstream is unavailable"),
+            RustKataError::Unsupported => write!(f, "not supported by the registry backend"),
+            RustKataError::InvalidInput { description } => write!(f, "{}", description),
+            RustKataError::Internal => write!(f, "unexpected error"),
+        }
+    }
+}
+
+impl std::error::Error for RustKataError {}
+
+impl ResponseError for RustKataError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RustKataError::NotFound => StatusCode::NOT_FOUND,
+            RustKataError::UpstreamStatus { status, .. } => match StatusCode::from_u16(*status) {
+                // upstream refusing our credentials is our problem, not the client's
+                Ok(StatusCode::UNAUTHORIZED)
+                | Ok(StatusCode::FORBIDDEN)
+                | Ok(StatusCode::PROXY_AUTHENTICATION_REQUIRED) => StatusCode::BAD_GATEWAY,
+                Ok(status) if status.is_client_error() => status,
+                _ => StatusCode::BAD_GATEWAY,
+            },
+            RustKataError::Transport
+            | RustKataError::Deserialization
+            | RustKataError::Integrity => StatusCode::BAD_GATEWAY,
+            RustKataError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            RustKataError::RateLimited { .. } | RustKataError::Unavailable => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            RustKataError::Unsupported => StatusCode::NOT_IMPLEMENTED,
+            RustKataError::InvalidInput { .. } => StatusCode::BAD_REQUEST,
+            RustKataError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        metrics::error_count(self.kind());
+
+        let mut response = http::problem(self.status_code(), self.kind(), &self.to_string());
+
+        let retry_after = match self {
+            RustKataError::RateLimited { retry_after } => Some(retry_after),
+            RustKataError::UpstreamStatus { retry_after, .. } => retry_after.as_ref(),
+            _ => None,
+        };
+
+        if let Some(retry_after) = retry_after {
+            if let Ok(value) = format!("{}", retry_after.as_secs_f64().ceil()).parse() {
+                response
+                    .headers_mut()
+                    .insert(actix_web::http::header::RETRY_AFTER, value);
+            }
+        }
+
+        response
+    }
+}
 
 pub type RustKataResult<T> = Result<T, RustKataError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::{Body, ResponseBody};
+
+    #[test]
+    fn test_error_response_is_problem() {
+        let response = RustKataError::UpstreamStatus {
+            status: 500,
+            retry_after: None,
+        }
+        .error_response();
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/problem+json"
+        );
+
+        let body = match response.body() {
+            ResponseBody::Body(Body::Bytes(bytes)) => bytes.clone(),
+            _ => panic!("expected a bytes body"),
+        };
+        let problem: http::ProblemResponse = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(problem.type_, "about:blank");
+        assert_eq!(problem.title, "Bad Gateway");
+        assert_eq!(problem.status, 502);
+        assert_eq!(problem.detail, "upstream responded with status 500");
+        assert_eq!(problem.error.code, "upstream_status");
+        assert_eq!(problem.error.description, problem.detail);
+    }
+
+    #[test]
+    fn test_rate_limited_sets_retry_after() {
+        let response = RustKataError::RateLimited {
+            retry_after: Duration::from_millis(1500),
+        }
+        .error_response();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers().get("retry-after").unwrap(), "2");
+    }
+
+    #[test]
+    fn test_upstream_client_errors_forwarded() {
+        let response = RustKataError::UpstreamStatus {
+            status: 429,
+            retry_after: Some(Duration::from_secs(30)),
+        }
+        .error_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get("retry-after").unwrap(), "30");
+
+        for (status, expected) in &[
+            (410, StatusCode::GONE),
+            (403, StatusCode::BAD_GATEWAY),
+            (401, StatusCode::BAD_GATEWAY),
+            (503, StatusCode::BAD_GATEWAY),
+        ] {
+            let error = RustKataError::UpstreamStatus {
+                status: *status,
+                retry_after: None,
+            };
+
+            assert_eq!(error.status_code(), *expected, "{}", status);
+        }
+    }
+}
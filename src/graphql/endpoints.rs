@@ -0,0 +1,26 @@
+use crate::graphql::loader::Loader;
+use crate::graphql::schema::{schema, RegistrySchema};
+use crate::interfaces::http;
+use crate::registry::registries::Registries;
+use actix_web::{web, HttpResponse};
+
+pub fn config(cfg: &mut web::ServiceConfig, host_base_path: &str) {
+    let path = format!("{}/graphql", host_base_path);
+
+    cfg.service(
+        web::scope(&path)
+            .data(schema())
+            .app_data(http::json_config())
+            .route("", web::post().to(query)),
+    );
+}
+
+async fn query(
+    web::Json(request): web::Json<async_graphql::Request>,
+    schema: web::Data<RegistrySchema>,
+    registries: web::Data<Registries>,
+) -> HttpResponse {
+    let response = schema.execute(request.data(Loader::new(registries))).await;
+
+    HttpResponse::Ok().json(response)
+}
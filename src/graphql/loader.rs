@@ -0,0 +1,93 @@
+use crate::interfaces::crate_registry::{get_crate, get_crate_dependencies};
+use crate::registry::registries::Registries;
+use actix_web::web;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+type Load<T> = Shared<BoxFuture<'static, async_graphql::Result<Arc<T>>>>;
+
+/// Per-request loader for the default registry. Every distinct key is fetched at most once,
+/// and concurrent resolvers asking for the same key await the same in-flight call.
+pub struct Loader {
+    registries: web::Data<Registries>,
+    crates: Mutex<HashMap<String, Load<get_crate::Response>>>,
+    dependencies: Mutex<HashMap<(String, String), Load<get_crate_dependencies::Response>>>,
+}
+
+impl Loader {
+    pub fn new(registries: web::Data<Registries>) -> Self {
+        Loader {
+            registries,
+            crates: Mutex::new(HashMap::new()),
+            dependencies: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn crate_(
+        &self,
+        crate_name: &str,
+    ) -> async_graphql::Result<Arc<get_crate::Response>> {
+        let registries = self.registries.clone();
+        let crate_name_owned = crate_name.to_owned();
+
+        load(&self.crates, crate_name.to_owned(), async move {
+            registries
+                .default_registry()
+                .get_crate(&crate_name_owned)
+                .await
+                .map_err(|err| format!("get_crate {}: {}", crate_name_owned, err).into())
+        })
+        .await
+    }
+
+    pub async fn dependencies(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> async_graphql::Result<Arc<get_crate_dependencies::Response>> {
+        let registries = self.registries.clone();
+        let crate_name_owned = crate_name.to_owned();
+        let crate_version_owned = crate_version.to_owned();
+
+        load(
+            &self.dependencies,
+            (crate_name.to_owned(), crate_version.to_owned()),
+            async move {
+                registries
+                    .default_registry()
+                    .get_crate_dependencies(&crate_name_owned, &crate_version_owned)
+                    .await
+                    .map_err(|err| {
+                        format!(
+                            "get_crate_dependencies {} {}: {}",
+                            crate_name_owned, crate_version_owned, err
+                        )
+                        .into()
+                    })
+            },
+        )
+        .await
+    }
+}
+
+async fn load<K, T, F>(
+    loads: &Mutex<HashMap<K, Load<T>>>,
+    key: K,
+    fetch: F,
+) -> async_graphql::Result<Arc<T>>
+where
+    K: Eq + Hash,
+    T: Send + Sync + 'static,
+    F: std::future::Future<Output = async_graphql::Result<T>> + Send + 'static,
+{
+    let load = loads
+        .lock()
+        .unwrap()
+        .entry(key)
+        .or_insert_with(|| fetch.map(|result| result.map(Arc::new)).boxed().shared())
+        .clone();
+
+    load.await
+}
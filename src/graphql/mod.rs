@@ -0,0 +1,3 @@
+pub mod endpoints;
+pub mod loader;
+pub mod schema;
@@ -0,0 +1,409 @@
+use crate::dependency_graph::requirements::{activated_dependencies, matching_version};
+use crate::graphql::loader::Loader;
+use crate::interfaces::crate_registry::get_crate;
+use crate::interfaces::crate_registry::get_crate_dependencies::DependencyResponse;
+use crate::interfaces::validation;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Result, SimpleObject};
+use futures::future;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+pub type RegistrySchema = async_graphql::Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// Deepest nesting of fields a query may have.
+pub const MAX_DEPTH: usize = 12;
+
+/// Most a query may cost, where every field costs 1, and fields that list versions or walk
+/// dependencies cost what they select many times over, as each item can call upstream.
+pub const MAX_COMPLEXITY: usize = 1000;
+
+pub fn schema() -> RegistrySchema {
+    async_graphql::Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    #[graphql(name = "crate")]
+    async fn crate_(&self, ctx: &Context<'_>, name: String) -> Result<Crate> {
+        validation::crate_name(&name)?;
+        let response = ctx.data_unchecked::<Loader>().crate_(&name).await?;
+        Ok(Crate(response))
+    }
+}
+
+pub struct Crate(Arc<get_crate::Response>);
+
+#[Object]
+impl Crate {
+    async fn name(&self) -> &str {
+        &self.0.crate_.name
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.crate_.description.as_deref()
+    }
+
+    async fn homepage(&self) -> Option<&str> {
+        self.0.crate_.homepage.as_deref()
+    }
+
+    async fn documentation(&self) -> Option<&str> {
+        self.0.crate_.documentation.as_deref()
+    }
+
+    async fn repository(&self) -> Option<&str> {
+        self.0.crate_.repository.as_deref()
+    }
+
+    async fn downloads(&self) -> i64 {
+        self.0.crate_.downloads
+    }
+
+    async fn recent_downloads(&self) -> i64 {
+        self.0.crate_.recent_downloads
+    }
+
+    async fn max_version(&self) -> &str {
+        &self.0.crate_.max_version
+    }
+
+    async fn newest_version(&self) -> &str {
+        &self.0.crate_.newest_version
+    }
+
+    async fn keywords(&self) -> Vec<Keyword> {
+        self.0
+            .keywords
+            .iter()
+            .map(|keyword| Keyword {
+                id: keyword.id.to_owned(),
+                keyword: keyword.keyword.to_owned(),
+                crates_cnt: keyword.crates_cnt,
+            })
+            .collect()
+    }
+
+    async fn categories(&self) -> Vec<Category> {
+        self.0
+            .categories
+            .iter()
+            .map(|category| Category {
+                id: category.id.to_owned(),
+                category: category.category.to_owned(),
+                slug: category.slug.to_owned(),
+                description: category.description.to_owned(),
+                crates_cnt: category.crates_cnt,
+            })
+            .collect()
+    }
+
+    #[graphql(complexity = "10 * child_complexity")]
+    async fn versions(&self) -> Vec<Version> {
+        (0..self.0.versions.len())
+            .map(|index| Version {
+                crate_: self.0.clone(),
+                index,
+            })
+            .collect()
+    }
+
+    async fn version(&self, num: String) -> Option<Version> {
+        self.0
+            .versions
+            .iter()
+            .position(|version| version.num == num)
+            .map(|index| Version {
+                crate_: self.0.clone(),
+                index,
+            })
+    }
+}
+
+pub struct Version {
+    crate_: Arc<get_crate::Response>,
+    index: usize,
+}
+
+impl Version {
+    fn response(&self) -> &get_crate::VersionResponse {
+        &self.crate_.versions[self.index]
+    }
+}
+
+#[Object]
+impl Version {
+    #[graphql(name = "crate")]
+    async fn crate_(&self) -> Crate {
+        Crate(self.crate_.clone())
+    }
+
+    async fn num(&self) -> &str {
+        &self.response().num
+    }
+
+    async fn yanked(&self) -> bool {
+        self.response().yanked
+    }
+
+    async fn license(&self) -> Option<&str> {
+        self.response().license.as_deref()
+    }
+
+    async fn downloads(&self) -> i64 {
+        self.response().downloads
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.response().created_at
+    }
+
+    async fn crate_size(&self) -> Option<i64> {
+        self.response().crate_size
+    }
+
+    async fn checksum(&self) -> Option<&str> {
+        self.response().checksum.as_deref()
+    }
+
+    async fn rust_version(&self) -> Option<&str> {
+        self.response().rust_version.as_deref()
+    }
+
+    async fn features(&self) -> Vec<Feature> {
+        let mut features: Vec<Feature> = self
+            .response()
+            .features
+            .iter()
+            .map(|(name, values)| Feature {
+                name: name.to_owned(),
+                values: values.to_owned(),
+            })
+            .collect();
+        features.sort_by(|a, b| a.name.cmp(&b.name));
+        features
+    }
+
+    /// Dependencies of this version, walked transitively up to `depth` levels. Without
+    /// `features` every dependency is returned, at every level; with `features` optional
+    /// dependencies are only returned when one of the features activates them, and those
+    /// of dependencies when the features they are asked for do. Dev-dependencies are returned
+    /// but, as in the dependency graph, not walked into.
+    ///
+    /// Each level is loaded at once: the dependencies of all its versions, then the crates
+    /// they resolve against, rather than one call after another.
+    #[graphql(complexity = "10 * depth.max(1) as usize * child_complexity")]
+    async fn dependencies(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 1)] depth: i32,
+        kinds: Option<Vec<String>>,
+        features: Option<Vec<String>>,
+    ) -> Result<Vec<Dependency>> {
+        let loader = ctx.data_unchecked::<Loader>();
+
+        let mut dependencies = Vec::new();
+        let mut visited = HashSet::new();
+        let mut frontier = vec![(
+            self.crate_.crate_.name.to_owned(),
+            self.response().num.to_owned(),
+            features
+                .as_ref()
+                .map(|features| activated_dependencies(&self.response().features, features)),
+        )];
+
+        for level in 1..=depth.max(0) {
+            let responses =
+                future::try_join_all(frontier.iter().map(|(crate_name, crate_version, _)| {
+                    loader.dependencies(crate_name, crate_version)
+                }))
+                .await?;
+
+            let mut walked = Vec::new();
+
+            for ((crate_name, crate_version, activated), response) in
+                frontier.iter().zip(&responses)
+            {
+                for dependency in &response.dependencies {
+                    if let Some(kinds) = &kinds {
+                        if !kinds.contains(&dependency.kind) {
+                            continue;
+                        }
+                    }
+
+                    if let Some(activated) = activated {
+                        if dependency.optional && !activated.contains(&dependency.crate_id) {
+                            continue;
+                        }
+                    }
+
+                    dependencies.push(Dependency {
+                        response: dependency.clone(),
+                        dependent: crate_name.to_owned(),
+                        dependent_version: crate_version.to_owned(),
+                        depth: level,
+                    });
+
+                    if level == depth || dependency.kind == "dev" {
+                        continue;
+                    }
+
+                    walked.push(dependency);
+                }
+            }
+
+            // the loader fetches a crate asked for several times at once only once
+            let children = future::try_join_all(
+                walked
+                    .iter()
+                    .map(|dependency| loader.crate_(&dependency.crate_id)),
+            )
+            .await?;
+
+            let mut next = Vec::new();
+
+            for (dependency, child) in walked.into_iter().zip(&children) {
+                let child_version = match matching_version(&dependency.req, &child.versions) {
+                    Some(child_version) => child_version,
+                    None => continue,
+                };
+
+                if !visited.insert((dependency.crate_id.to_owned(), child_version.to_owned())) {
+                    continue;
+                }
+
+                let child_features = match features {
+                    Some(_) => {
+                        let mut requested = dependency.features.clone().unwrap_or_default();
+                        if dependency.default_features {
+                            requested.push("default".to_owned());
+                        }
+                        child
+                            .versions
+                            .iter()
+                            .find(|version| version.num == child_version)
+                            .map(|version| activated_dependencies(&version.features, &requested))
+                    }
+                    None => None,
+                };
+
+                next.push((
+                    dependency.crate_id.to_owned(),
+                    child_version.to_owned(),
+                    child_features,
+                ));
+            }
+
+            frontier = next;
+        }
+
+        Ok(dependencies)
+    }
+}
+
+pub struct Dependency {
+    response: DependencyResponse,
+    dependent: String,
+    dependent_version: String,
+    depth: i32,
+}
+
+#[Object]
+impl Dependency {
+    async fn name(&self) -> &str {
+        &self.response.crate_id
+    }
+
+    async fn req(&self) -> &str {
+        &self.response.req
+    }
+
+    async fn kind(&self) -> &str {
+        &self.response.kind
+    }
+
+    async fn optional(&self) -> bool {
+        self.response.optional
+    }
+
+    async fn default_features(&self) -> bool {
+        self.response.default_features
+    }
+
+    async fn features(&self) -> Vec<String> {
+        self.response.features.clone().unwrap_or_default()
+    }
+
+    async fn target(&self) -> Option<&str> {
+        self.response.target.as_deref()
+    }
+
+    async fn dependent(&self) -> &str {
+        &self.dependent
+    }
+
+    async fn dependent_version(&self) -> &str {
+        &self.dependent_version
+    }
+
+    async fn depth(&self) -> i32 {
+        self.depth
+    }
+
+    #[graphql(name = "crate")]
+    async fn crate_(&self, ctx: &Context<'_>) -> Result<Crate> {
+        let response = ctx
+            .data_unchecked::<Loader>()
+            .crate_(&self.response.crate_id)
+            .await?;
+        Ok(Crate(response))
+    }
+
+    /// Highest published version satisfying `req`.
+    async fn version(&self, ctx: &Context<'_>) -> Result<Option<Version>> {
+        let response = ctx
+            .data_unchecked::<Loader>()
+            .crate_(&self.response.crate_id)
+            .await?;
+
+        let num = match matching_version(&self.response.req, &response.versions) {
+            Some(num) => num.to_owned(),
+            None => return Ok(None),
+        };
+
+        Ok(response
+            .versions
+            .iter()
+            .position(|version| version.num == num)
+            .map(|index| Version {
+                crate_: response.clone(),
+                index,
+            }))
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Keyword {
+    id: String,
+    keyword: String,
+    crates_cnt: i64,
+}
+
+#[derive(SimpleObject)]
+pub struct Category {
+    id: String,
+    category: String,
+    slug: String,
+    description: String,
+    crates_cnt: i64,
+}
+
+#[derive(SimpleObject)]
+pub struct Feature {
+    name: String,
+    values: Vec<String>,
+}
@@ -0,0 +1,169 @@
+use crate::crates_io::CratesIoClient;
+use crate::errors::{RustKataError, RustKataResult};
+use crate::index::models::{self, IndexEntry};
+use crate::interfaces::crate_registry::{get_crate, get_crate_dependencies, CrateRegistry};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+/// Registry backed by an index laid out like crates.io-index on the local filesystem, such
+/// as a checkout of a mirrored index. Downloads need a client for the host the `dl` of the
+/// index config points at, and are verified against the checksum of the index entry.
+pub struct LocalIndex {
+    root: PathBuf,
+    downloads: Option<CratesIoClient>,
+}
+
+impl LocalIndex {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        LocalIndex {
+            root: root.into(),
+            downloads: None,
+        }
+    }
+
+    pub fn downloads(mut self, downloads: CratesIoClient) -> Self {
+        self.downloads = Some(downloads);
+        self
+    }
+
+    async fn entries(&self, crate_name: &str) -> RustKataResult<Vec<IndexEntry>> {
+        models::parse(&read(&self.root.join(models::path(crate_name))).await?)
+    }
+}
+
+async fn read(path: &Path) -> RustKataResult<Vec<u8>> {
+    tokio::fs::read(path).await.map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => RustKataError::NotFound,
+        _ => {
+            log::warn!("failed to read {}: {}", path.display(), err);
+            RustKataError::Internal
+        }
+    })
+}
+
+#[async_trait]
+impl CrateRegistry for LocalIndex {
+    async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response> {
+        models::crate_response(&self.entries(crate_name).await?)
+    }
+
+    async fn get_crate_dependencies(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_dependencies::Response> {
+        models::dependencies_response(&self.entries(crate_name).await?, crate_version)
+    }
+
+    async fn download_verified(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+        checksum: &str,
+    ) -> RustKataResult<Vec<u8>> {
+        let downloads = self.downloads.as_ref().ok_or(RustKataError::Unsupported)?;
+
+        let config = models::parse_config(&read(&self.root.join("config.json")).await?)?;
+        let url = models::download_url(&config.dl, crate_name, crate_version, checksum);
+
+        downloads.download(&url, checksum).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_get_crate() {
+        let response = LocalIndex::new("./tests/fixtures/index")
+            .get_crate("Rand")
+            .await
+            .unwrap();
+
+        assert_eq!(response.crate_.name, "rand");
+        assert_eq!(response.crate_.newest_version, "0.8.2");
+        assert_eq!(response.versions.len(), 3);
+        assert_eq!(response.versions[2].num, "0.8.0");
+    }
+
+    #[actix_rt::test]
+    async fn test_get_crate_dependencies() {
+        let response = LocalIndex::new("./tests/fixtures/index")
+            .get_crate_dependencies("rand", "0.8.1")
+            .await
+            .unwrap();
+
+        assert_eq!(response.dependencies.len(), 10);
+        assert_eq!(response.dependencies[5].crate_id, "rand_core");
+        assert_eq!(response.dependencies[5].req, "^0.6.0");
+    }
+
+    #[actix_rt::test]
+    async fn test_get_crate_not_found() {
+        let index = LocalIndex::new("./tests/fixtures/index");
+
+        assert!(matches!(
+            index.get_crate("nonexistent").await,
+            Err(RustKataError::NotFound)
+        ));
+        assert!(matches!(
+            index.get_crate_dependencies("rand", "0.7.3").await,
+            Err(RustKataError::NotFound)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_download_crate() {
+        let root = given_packed_index(&format!(
+            "{}/local-dl/{{crate}}/{{crate}}-{{version}}.crate",
+            mockito::server_url()
+        ));
+        let download = mockito::mock("GET", "/local-dl/packed/packed-1.0.0.crate")
+            .with_status(200)
+            .with_body("crate")
+            .expect(1)
+            .create();
+
+        let body = LocalIndex::new(&root)
+            .downloads(CratesIoClient::new(&mockito::server_url()).unwrap())
+            .download_crate("packed", "1.0.0")
+            .await
+            .unwrap();
+
+        download.assert();
+        assert_eq!(body, b"crate");
+
+        assert!(matches!(
+            LocalIndex::new(&root)
+                .download_crate("packed", "1.0.0")
+                .await,
+            Err(RustKataError::Unsupported)
+        ));
+    }
+
+    /// Index holding a single `packed` crate, whose `.crate` file is `crate`.
+    fn given_packed_index(dl: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(root.join("pa/ck")).unwrap();
+        std::fs::write(
+            root.join("config.json"),
+            serde_json::json!({ "dl": dl }).to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("pa/ck/packed"),
+            serde_json::json!({
+                "name": "packed",
+                "vers": "1.0.0",
+                "deps": [],
+                "cksum": crate::registry::artifacts::sha256(b"crate"),
+                "features": {},
+                "yanked": false
+            })
+            .to_string(),
+        )
+        .unwrap();
+        root
+    }
+}
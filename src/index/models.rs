@@ -0,0 +1,377 @@
+use crate::errors::{RustKataError, RustKataResult};
+use crate::interfaces::crate_registry::{get_crate, get_crate_dependencies};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One line of a crate's index file, describing a single published version.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IndexEntry {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "vers")]
+    pub vers: String,
+    #[serde(rename = "deps")]
+    pub deps: Vec<IndexDependency>,
+    #[serde(rename = "cksum")]
+    pub cksum: String,
+    #[serde(rename = "features")]
+    pub features: HashMap<String, Vec<String>>,
+    #[serde(default, rename = "features2")]
+    pub features2: Option<HashMap<String, Vec<String>>>,
+    #[serde(rename = "yanked")]
+    pub yanked: bool,
+    #[serde(default, rename = "links")]
+    pub links: Option<String>,
+    #[serde(default, rename = "rust_version")]
+    pub rust_version: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IndexDependency {
+    /// Name the dependency is known by in the dependent, which differs from the crate's own
+    /// name when it is renamed.
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "req")]
+    pub req: String,
+    #[serde(rename = "features")]
+    pub features: Vec<String>,
+    #[serde(rename = "optional")]
+    pub optional: bool,
+    #[serde(rename = "default_features")]
+    pub default_features: bool,
+    #[serde(default, rename = "target")]
+    pub target: Option<String>,
+    #[serde(default, rename = "kind")]
+    pub kind: Option<String>,
+    #[serde(default, rename = "registry")]
+    pub registry: Option<String>,
+    #[serde(default, rename = "package")]
+    pub package: Option<String>,
+}
+
+/// `config.json` at the root of an index.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IndexConfig {
+    /// Where `.crate` files are downloaded from, see [`download_url`].
+    #[serde(rename = "dl")]
+    pub dl: String,
+}
+
+pub fn parse_config(body: &[u8]) -> RustKataResult<IndexConfig> {
+    serde_json::from_slice(body).map_err(|_| RustKataError::Deserialization)
+}
+
+/// Url of a version's `.crate` file, following the `dl` template of the index config like
+/// cargo does: without any marker `dl` is the root of `/{crate}/{version}/download` urls.
+pub fn download_url(dl: &str, crate_name: &str, crate_version: &str, checksum: &str) -> String {
+    const MARKERS: &[&str] = &[
+        "{crate}",
+        "{version}",
+        "{prefix}",
+        "{lowerprefix}",
+        "{sha256-checksum}",
+    ];
+
+    if !MARKERS.iter().any(|marker| dl.contains(marker)) {
+        return format!(
+            "{}/{}/{}/download",
+            dl.trim_end_matches('/'),
+            crate_name,
+            crate_version
+        );
+    }
+
+    let prefix = match crate_name.len() {
+        1 => "1".to_owned(),
+        2 => "2".to_owned(),
+        3 => format!("3/{}", &crate_name[..1]),
+        _ => format!("{}/{}", &crate_name[..2], &crate_name[2..4]),
+    };
+
+    dl.replace("{crate}", crate_name)
+        .replace("{version}", crate_version)
+        .replace("{prefix}", &prefix)
+        .replace("{lowerprefix}", &prefix.to_lowercase())
+        .replace("{sha256-checksum}", checksum)
+}
+
+/// Location of a crate's file relative to the index root.
+pub fn path(crate_name: &str) -> String {
+    let crate_name = crate_name.to_lowercase();
+
+    match crate_name.len() {
+        1 => format!("1/{}", crate_name),
+        2 => format!("2/{}", crate_name),
+        3 => format!("3/{}/{}", &crate_name[..1], crate_name),
+        _ => format!("{}/{}/{}", &crate_name[..2], &crate_name[2..4], crate_name),
+    }
+}
+
+pub fn parse(body: &[u8]) -> RustKataResult<Vec<IndexEntry>> {
+    body.split(|byte| *byte == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .map(|line| serde_json::from_slice(line).map_err(|_| RustKataError::Deserialization))
+        .collect()
+}
+
+/// The index only knows what cargo needs to resolve, so everything else in the response,
+/// such as downloads and descriptions, is left empty.
+pub fn crate_response(entries: &[IndexEntry]) -> RustKataResult<get_crate::Response> {
+    let newest = entries.last().ok_or(RustKataError::NotFound)?;
+    let name = newest.name.clone();
+
+    // the api lists versions newest first
+    let versions: Vec<get_crate::VersionResponse> = entries
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(index, entry)| version_response(index, entry))
+        .collect();
+
+    Ok(get_crate::Response {
+        crate_: get_crate::CrateResponse {
+            id: name.clone(),
+            name: name.clone(),
+            updated_at: String::new(),
+            versions: versions.iter().map(|version| version.id).collect(),
+            keywords: vec![],
+            categories: vec![],
+            badges: vec![],
+            created_at: String::new(),
+            downloads: 0,
+            recent_downloads: 0,
+            max_version: max_version(
+                entries
+                    .iter()
+                    .map(|entry| (entry.vers.as_str(), entry.yanked)),
+            )
+            .to_owned(),
+            newest_version: newest.vers.clone(),
+            description: None,
+            homepage: None,
+            documentation: None,
+            repository: None,
+            links: crate_links(&name),
+            exact_match: false,
+            extra: Default::default(),
+        },
+        versions,
+        keywords: vec![],
+        categories: vec![],
+        served_by: None,
+        upstream: Default::default(),
+        extra: Default::default(),
+    })
+}
+
+pub fn dependencies_response(
+    entries: &[IndexEntry],
+    crate_version: &str,
+) -> RustKataResult<get_crate_dependencies::Response> {
+    let (index, entry) = entries
+        .iter()
+        .enumerate()
+        .find(|(_, entry)| entry.vers == crate_version)
+        .ok_or(RustKataError::NotFound)?;
+
+    Ok(get_crate_dependencies::Response {
+        dependencies: entry
+            .deps
+            .iter()
+            .enumerate()
+            .map(
+                |(dependency_index, dependency)| get_crate_dependencies::DependencyResponse {
+                    id: dependency_index as i64 + 1,
+                    version_id: version_id(index),
+                    crate_id: dependency
+                        .package
+                        .clone()
+                        .unwrap_or_else(|| dependency.name.clone()),
+                    req: dependency.req.clone(),
+                    optional: dependency.optional,
+                    default_features: dependency.default_features,
+                    features: Some(dependency.features.clone()),
+                    target: dependency.target.clone(),
+                    kind: dependency
+                        .kind
+                        .clone()
+                        .unwrap_or_else(|| "normal".to_owned()),
+                    downloads: 0,
+                    registry: dependency.registry.clone(),
+                    extra: Default::default(),
+                },
+            )
+            .collect(),
+        served_by: None,
+        upstream: Default::default(),
+        extra: Default::default(),
+    })
+}
+
+/// The index has no ids, so versions are numbered in the order they were published.
+fn version_id(index: usize) -> i64 {
+    index as i64 + 1
+}
+
+fn version_response(index: usize, entry: &IndexEntry) -> get_crate::VersionResponse {
+    let mut features = entry.features.clone();
+    if let Some(features2) = &entry.features2 {
+        features.extend(features2.clone());
+    }
+
+    get_crate::VersionResponse {
+        id: version_id(index),
+        crate_: entry.name.clone(),
+        num: entry.vers.clone(),
+        dl_path: format!("/api/v1/crates/{}/{}/download", entry.name, entry.vers),
+        readme_path: format!("/api/v1/crates/{}/{}/readme", entry.name, entry.vers),
+        updated_at: String::new(),
+        created_at: String::new(),
+        downloads: 0,
+        features,
+        yanked: entry.yanked,
+        license: None,
+        links: version_links(&entry.name, &entry.vers),
+        crate_size: None,
+        published_by: None,
+        audit_actions: vec![],
+        checksum: Some(entry.cksum.clone()),
+        rust_version: entry.rust_version.clone(),
+        extra: Default::default(),
+    }
+}
+
+/// Links the crates.io api would have returned for a crate.
+pub(crate) fn crate_links(crate_name: &str) -> get_crate::CrateLinksResponse {
+    get_crate::CrateLinksResponse {
+        version_downloads: format!("/api/v1/crates/{}/downloads", crate_name),
+        versions: None,
+        owners: Some(format!("/api/v1/crates/{}/owners", crate_name)),
+        owner_team: Some(format!("/api/v1/crates/{}/owner_team", crate_name)),
+        owner_user: format!("/api/v1/crates/{}/owner_user", crate_name),
+        reverse_dependencies: format!("/api/v1/crates/{}/reverse_dependencies", crate_name),
+        extra: Default::default(),
+    }
+}
+
+/// Links the crates.io api would have returned for a version.
+pub(crate) fn version_links(crate_name: &str, num: &str) -> get_crate::VersionLinksResponse {
+    get_crate::VersionLinksResponse {
+        dependencies: format!("/api/v1/crates/{}/{}/dependencies", crate_name, num),
+        version_downloads: format!("/api/v1/crates/{}/{}/downloads", crate_name, num),
+        authors: format!("/api/v1/crates/{}/{}/authors", crate_name, num),
+        extra: Default::default(),
+    }
+}
+
+/// Highest stable version that is not yanked, like crates.io reports it, falling back to
+/// the highest version of all.
+pub(crate) fn max_version<'a, I>(versions: I) -> &'a str
+where
+    I: IntoIterator<Item = (&'a str, bool)>,
+{
+    let parsed: Vec<(semver::Version, &str, bool)> = versions
+        .into_iter()
+        .filter_map(|(num, yanked)| {
+            semver::Version::parse(num)
+                .ok()
+                .map(|version| (version, num, yanked))
+        })
+        .collect();
+
+    parsed
+        .iter()
+        .filter(|(version, _, yanked)| version.pre.is_empty() && !yanked)
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .or_else(|| parsed.iter().max_by(|a, b| a.0.cmp(&b.0)))
+        .map(|(_, num, _)| *num)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        assert_eq!(path("a"), "1/a");
+        assert_eq!(path("cc"), "2/cc");
+        assert_eq!(path("syn"), "3/s/syn");
+        assert_eq!(path("rand"), "ra/nd/rand");
+        assert_eq!(path("Inflector"), "in/fl/inflector");
+    }
+
+    #[test]
+    fn test_download_url() {
+        assert_eq!(
+            download_url("https://static.crates.io/crates", "rand", "0.8.2", "ab"),
+            "https://static.crates.io/crates/rand/0.8.2/download"
+        );
+        assert_eq!(
+            download_url(
+                "https://example.com/{prefix}/{lowerprefix}/{crate}-{version}.crate?{sha256-checksum}",
+                "Serde",
+                "1.0.0",
+                "ab"
+            ),
+            "https://example.com/Se/rd/se/rd/Serde-1.0.0.crate?ab"
+        );
+        assert_eq!(
+            download_url("https://example.com/{prefix}/{crate}", "syn", "1.0.0", "ab"),
+            "https://example.com/3/s/syn"
+        );
+    }
+
+    #[test]
+    fn test_crate_response() {
+        let mut entries = entries();
+        entries.push(IndexEntry {
+            vers: "0.9.0-alpha.1".to_owned(),
+            rust_version: Some("1.56".to_owned()),
+            ..entries[2].clone()
+        });
+        entries[2].yanked = true;
+
+        let response = crate_response(&entries).unwrap();
+
+        assert_eq!(response.crate_.name, "rand");
+        assert_eq!(response.crate_.max_version, "0.8.1");
+        assert_eq!(response.crate_.newest_version, "0.9.0-alpha.1");
+        assert_eq!(response.crate_.versions, vec![4, 3, 2, 1]);
+
+        assert_eq!(response.versions[0].num, "0.9.0-alpha.1");
+        assert_eq!(response.versions[0].rust_version, Some("1.56".to_owned()));
+        assert!(response.versions[1].yanked);
+        assert_eq!(response.versions[3].num, "0.8.0");
+        assert_eq!(
+            response.versions[3].features["default"],
+            vec!["std", "std_rng"]
+        );
+        assert_eq!(response.versions[3].checksum.as_ref().unwrap().len(), 64);
+    }
+
+    #[test]
+    fn test_dependencies_response() {
+        let response = dependencies_response(&entries(), "0.8.2").unwrap();
+
+        assert_eq!(response.dependencies.len(), 10);
+
+        let packed_simd = response
+            .dependencies
+            .iter()
+            .find(|dependency| dependency.crate_id == "packed_simd_2")
+            .unwrap();
+        assert_eq!(packed_simd.version_id, 3);
+        assert_eq!(packed_simd.req, "^0.3.4");
+        assert!(packed_simd.optional);
+        assert_eq!(packed_simd.features, Some(vec!["into_bits".to_owned()]));
+        assert_eq!(packed_simd.kind, "normal");
+
+        assert!(dependencies_response(&entries(), "0.7.3").is_err());
+    }
+
+    fn entries() -> Vec<IndexEntry> {
+        parse(&std::fs::read("./tests/fixtures/index/ra/nd/rand").unwrap()).unwrap()
+    }
+}
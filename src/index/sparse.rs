@@ -0,0 +1,212 @@
+use crate::crates_io::CratesIoClient;
+use crate::errors::RustKataResult;
+use crate::index::models::{self, IndexEntry};
+use crate::interfaces::crate_registry::{get_crate, get_crate_dependencies, CrateRegistry};
+use async_trait::async_trait;
+
+/// Registry backed by the sparse index protocol, where every crate is a single file of
+/// newline-delimited JSON, one line per published version. Downloads are verified against
+/// the checksum of the version's index entry.
+pub struct SparseIndexClient {
+    client: CratesIoClient,
+    downloads: Option<CratesIoClient>,
+}
+
+impl SparseIndexClient {
+    /// `client` points at the root of the index, e.g. `https://index.crates.io`.
+    pub fn new(client: CratesIoClient) -> Self {
+        SparseIndexClient {
+            client,
+            downloads: None,
+        }
+    }
+
+    /// Client for the host `.crate` files are downloaded from, when the `dl` of the index
+    /// config points away from the index, e.g. `https://static.crates.io` for crates.io.
+    pub fn downloads(mut self, downloads: CratesIoClient) -> Self {
+        self.downloads = Some(downloads);
+        self
+    }
+
+    async fn entries(&self, crate_name: &str, endpoint: &str) -> RustKataResult<Vec<IndexEntry>> {
+        let path = format!("/{}", models::path(crate_name));
+
+        // new versions are appended to the file, so it is always revalidated
+        models::parse(&self.client.fetch(&path, endpoint, false).await?)
+    }
+}
+
+#[async_trait]
+impl CrateRegistry for SparseIndexClient {
+    async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response> {
+        models::crate_response(&self.entries(crate_name, "get_crate").await?)
+    }
+
+    async fn get_crate_dependencies(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_dependencies::Response> {
+        models::dependencies_response(
+            &self.entries(crate_name, "get_crate_dependencies").await?,
+            crate_version,
+        )
+    }
+
+    async fn download_verified(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+        checksum: &str,
+    ) -> RustKataResult<Vec<u8>> {
+        let config = models::parse_config(
+            &self
+                .client
+                .fetch("/config.json", "download_crate", false)
+                .await?,
+        )?;
+        let url = models::download_url(&config.dl, crate_name, crate_version, checksum);
+
+        match &self.downloads {
+            Some(downloads) if downloads.serves(&url) => downloads.download(&url, checksum).await,
+            _ => self.client.download(&url, checksum).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crates_io::retry::RetryOptions;
+    use crate::errors::RustKataError;
+    use mockito::mock;
+
+    #[actix_rt::test]
+    async fn test_get_crate() {
+        let mock = given_index_file();
+
+        let response = client().get_crate("rand").await.unwrap();
+
+        mock.assert();
+
+        assert_eq!(response.crate_.name, "rand");
+        assert_eq!(response.crate_.max_version, "0.8.2");
+        assert_eq!(response.versions.len(), 3);
+        assert_eq!(response.versions[0].num, "0.8.2");
+        assert!(!response.versions[0].yanked);
+        assert_eq!(
+            response.versions[0].features["serde1"],
+            vec!["serde".to_owned()]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_get_crate_dependencies() {
+        let mock = given_index_file();
+
+        let response = client()
+            .get_crate_dependencies("rand", "0.8.2")
+            .await
+            .unwrap();
+
+        mock.assert();
+
+        assert_eq!(response.dependencies.len(), 10);
+        assert_eq!(response.dependencies[0].crate_id, "bincode");
+        assert_eq!(response.dependencies[0].kind, "dev");
+        assert_eq!(response.dependencies[1].crate_id, "libc");
+        assert_eq!(
+            response.dependencies[1].target,
+            Some("cfg(unix)".to_owned())
+        );
+        assert!(!response.dependencies[1].default_features);
+    }
+
+    #[actix_rt::test]
+    async fn test_get_crate_not_found() {
+        let mock = mock("GET", "/no/ne/nonexistent")
+            .with_status(404)
+            .expect(1)
+            .create();
+
+        assert!(matches!(
+            client().get_crate("nonexistent").await,
+            Err(RustKataError::NotFound)
+        ));
+
+        mock.assert();
+    }
+
+    #[actix_rt::test]
+    async fn test_download_crate() {
+        let _config = given_config(&format!("{}/sparse-dl", mockito::server_url()));
+        let _index = given_packed_index_file();
+        let download = mock("GET", "/sparse-dl/packed/1.0.0/download")
+            .with_status(200)
+            .with_body("crate")
+            .expect(1)
+            .create();
+
+        let body = client().download_crate("packed", "1.0.0").await.unwrap();
+
+        download.assert();
+        assert_eq!(body, b"crate");
+    }
+
+    #[actix_rt::test]
+    async fn test_download_crate_elsewhere_needs_a_client() {
+        let _config = given_config("https://static.crates.io/crates");
+        let _index = given_packed_index_file();
+
+        assert!(matches!(
+            client().download_crate("packed", "1.0.0").await,
+            Err(RustKataError::Unsupported)
+        ));
+    }
+
+    fn given_config(dl: &str) -> mockito::Mock {
+        mock("GET", "/config.json")
+            .with_status(200)
+            .with_body(serde_json::json!({ "dl": dl }).to_string())
+            .create()
+    }
+
+    fn given_packed_index_file() -> mockito::Mock {
+        mock("GET", "/pa/ck/packed")
+            .with_status(200)
+            .with_body(
+                serde_json::json!({
+                    "name": "packed",
+                    "vers": "1.0.0",
+                    "deps": [],
+                    "cksum": crate::registry::artifacts::sha256(b"crate"),
+                    "features": {},
+                    "yanked": false
+                })
+                .to_string(),
+            )
+            .create()
+    }
+
+    fn given_index_file() -> mockito::Mock {
+        mock("GET", "/ra/nd/rand")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body(std::fs::read_to_string("./tests/fixtures/index/ra/nd/rand").unwrap())
+            .match_header(
+                "user-agent",
+                "rust-kata-002 (https://github.com/agabani/rust-kata-002)",
+            )
+            .expect(1)
+            .create()
+    }
+
+    fn client() -> SparseIndexClient {
+        SparseIndexClient::new(CratesIoClient::new(&mockito::server_url()).unwrap().retry(
+            RetryOptions {
+                max_retries: 0,
+                ..RetryOptions::default()
+            },
+        ))
+    }
+}
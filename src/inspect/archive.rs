@@ -0,0 +1,282 @@
+use crate::errors::{RustKataError, RustKataResult};
+use crate::inspect::models::{File, InspectResult, Manifest};
+use crate::inspect::scanner;
+use flate2::read::GzDecoder;
+use std::io::Read;
+use std::path::{Component, Path};
+
+/// Most bytes a package may unpack to. crates.io caps uploads at 10 MiB compressed.
+const MAX_UNPACKED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Unpacks a `.crate` file in memory and reports what a reviewer would look at first.
+pub fn inspect(body: &[u8]) -> RustKataResult<InspectResult> {
+    let mut files = Vec::new();
+    let mut cargo_toml = None;
+    let mut unpacked = 0;
+
+    let mut archive = tar::Archive::new(GzDecoder::new(body));
+    for entry in archive.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let size = entry.header().size().map_err(invalid)?;
+        unpacked += size;
+        if unpacked > MAX_UNPACKED_BYTES {
+            return Err(RustKataError::InvalidInput {
+                description: format!(
+                    "crate unpacks to more than {} MiB, too large to inspect",
+                    MAX_UNPACKED_BYTES / 1024 / 1024
+                ),
+            });
+        }
+
+        // files live in `<name>-<version>/`
+        let path = match package_path(&entry.path().map_err(invalid)?) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        let mut file = File {
+            path,
+            size,
+            unsafe_blocks: None,
+            extern_declarations: None,
+        };
+
+        if file.path == "Cargo.toml" {
+            let mut content = String::new();
+            entry.read_to_string(&mut content).map_err(invalid)?;
+            cargo_toml = Some(content);
+        } else if file.path.ends_with(".rs") {
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).map_err(invalid)?;
+            let counts = scanner::scan(&String::from_utf8_lossy(&content));
+            file.unsafe_blocks = Some(counts.unsafe_blocks);
+            file.extern_declarations = Some(counts.extern_declarations);
+        }
+
+        files.push(file);
+    }
+
+    let build_rs = files.iter().any(|file| file.path == "build.rs");
+
+    let manifest = manifest(
+        &cargo_toml.ok_or_else(|| {
+            log::warn!("crate has no Cargo.toml");
+            RustKataError::Deserialization
+        })?,
+        build_rs,
+    )?;
+
+    Ok(InspectResult {
+        manifest,
+        build_rs,
+        unsafe_blocks: files.iter().filter_map(|file| file.unsafe_blocks).sum(),
+        extern_declarations: files
+            .iter()
+            .filter_map(|file| file.extern_declarations)
+            .sum(),
+        files,
+    })
+}
+
+/// `path` without the `<name>-<version>` directory, or `None` for anything that would
+/// escape the package.
+fn package_path(path: &Path) -> Option<String> {
+    let mut components = path.components();
+    components.next()?;
+
+    let mut parts = Vec::new();
+    for component in components {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy()),
+            _ => return None,
+        }
+    }
+
+    match parts.is_empty() {
+        true => None,
+        false => Some(parts.join("/")),
+    }
+}
+
+fn manifest(cargo_toml: &str, build_rs: bool) -> RustKataResult<Manifest> {
+    let value: toml::Value = cargo_toml.parse().map_err(invalid)?;
+
+    // `project` is what `package` was called before cargo 0.13
+    let package = value
+        .get("package")
+        .or_else(|| value.get("project"))
+        .ok_or_else(|| {
+            log::warn!("Cargo.toml has no package");
+            RustKataError::Deserialization
+        })?;
+    let string = |key: &str| {
+        package
+            .get(key)
+            .and_then(toml::Value::as_str)
+            .map(str::to_owned)
+    };
+
+    // cargo runs `build.rs` unless told otherwise
+    let build = match package.get("build") {
+        Some(toml::Value::String(path)) => Some(path.to_owned()),
+        Some(toml::Value::Boolean(false)) => None,
+        _ if build_rs => Some("build.rs".to_owned()),
+        _ => None,
+    };
+
+    let proc_macro = value
+        .get("lib")
+        .and_then(|lib| lib.get("proc-macro").or_else(|| lib.get("proc_macro")))
+        .and_then(toml::Value::as_bool)
+        .unwrap_or(false);
+
+    Ok(Manifest {
+        name: string("name").unwrap_or_default(),
+        version: string("version").unwrap_or_default(),
+        edition: string("edition").unwrap_or_else(|| "2015".to_owned()),
+        rust_version: string("rust-version"),
+        links: string("links"),
+        build,
+        proc_macro,
+    })
+}
+
+fn invalid<E: std::fmt::Display>(err: E) -> RustKataError {
+    log::warn!("invalid crate: {}", err);
+    RustKataError::Deserialization
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    /// A `.crate` file of `files`, packed like cargo does under `<name>-<version>/`.
+    fn package(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    format!("inspected-0.1.0/{}", path),
+                    content.as_bytes(),
+                )
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_inspect() {
+        let result = inspect(&package(&[
+            (
+                "Cargo.toml",
+                r#"
+                [package]
+                edition = "2018"
+                rust-version = "1.56"
+                name = "inspected"
+                version = "0.1.0"
+                links = "z"
+
+                [lib]
+                proc-macro = true
+                "#,
+            ),
+            ("Cargo.toml.orig", "[package]\nname = \"inspected\"\n"),
+            ("build.rs", "fn main() {}\n"),
+            (
+                "src/lib.rs",
+                "pub fn f() { unsafe { g() } }\nextern \"C\" { fn g(); }\n",
+            ),
+            ("README.md", "# inspected\n"),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            result.manifest,
+            Manifest {
+                name: "inspected".to_owned(),
+                version: "0.1.0".to_owned(),
+                edition: "2018".to_owned(),
+                rust_version: Some("1.56".to_owned()),
+                links: Some("z".to_owned()),
+                build: Some("build.rs".to_owned()),
+                proc_macro: true,
+            }
+        );
+        assert!(result.build_rs);
+        assert_eq!(
+            result
+                .files
+                .iter()
+                .map(|file| file.path.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "Cargo.toml",
+                "Cargo.toml.orig",
+                "build.rs",
+                "src/lib.rs",
+                "README.md"
+            ]
+        );
+        assert_eq!(result.files[4].size, 12);
+        assert_eq!(result.files[4].unsafe_blocks, None);
+        assert_eq!(result.files[3].unsafe_blocks, Some(1));
+        assert_eq!(result.files[3].extern_declarations, Some(1));
+        assert_eq!(result.unsafe_blocks, 1);
+        assert_eq!(result.extern_declarations, 1);
+    }
+
+    #[test]
+    fn test_manifest_defaults() {
+        let manifest = manifest("[package]\nname = \"old\"\nversion = \"0.1.0\"\n", false).unwrap();
+
+        assert_eq!(manifest.edition, "2015");
+        assert_eq!(manifest.rust_version, None);
+        assert_eq!(manifest.build, None);
+        assert!(!manifest.proc_macro);
+
+        let manifest = manifest_with_build_rs("build = false");
+        assert_eq!(manifest.build, None);
+
+        let manifest = manifest_with_build_rs("build = \"src/build.rs\"");
+        assert_eq!(manifest.build.as_deref(), Some("src/build.rs"));
+    }
+
+    fn manifest_with_build_rs(build: &str) -> Manifest {
+        manifest(&format!("[package]\nname = \"built\"\n{}\n", build), true).unwrap()
+    }
+
+    #[test]
+    fn test_invalid_crates() {
+        assert!(matches!(
+            inspect(b"not a crate"),
+            Err(RustKataError::Deserialization)
+        ));
+        assert!(matches!(
+            inspect(&package(&[("src/lib.rs", "")])),
+            Err(RustKataError::Deserialization)
+        ));
+    }
+
+    #[test]
+    fn test_package_path() {
+        assert_eq!(
+            package_path(Path::new("rand-0.8.2/src/lib.rs")).as_deref(),
+            Some("src/lib.rs")
+        );
+        assert_eq!(package_path(Path::new("rand-0.8.2")), None);
+        assert_eq!(package_path(Path::new("rand-0.8.2/../etc/passwd")), None);
+    }
+}
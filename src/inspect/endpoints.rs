@@ -0,0 +1,45 @@
+use crate::errors::{RustKataError, RustKataResult};
+use crate::inspect::archive;
+use crate::inspect::models::QueryParams;
+use crate::interfaces::http;
+use crate::registry::registries::Registries;
+use actix_web::error::BlockingError;
+use actix_web::{web, HttpResponse};
+
+pub fn config(cfg: &mut web::ServiceConfig, host_base_path: &str) {
+    let path = format!("{}/inspect", host_base_path);
+
+    cfg.service(
+        web::scope(&path)
+            .app_data(http::query_config())
+            .route("", web::get().to(query)),
+    );
+}
+
+async fn query(
+    web::Query(query_parameters): web::Query<QueryParams>,
+    registries: web::Data<Registries>,
+) -> RustKataResult<HttpResponse> {
+    let crate_registry = match registries.get(query_parameters.registry.as_deref()) {
+        Some(crate_registry) => crate_registry,
+        None => {
+            return Ok(http::unknown_registry(
+                query_parameters.registry.as_deref().unwrap_or_default(),
+            ))
+        }
+    };
+
+    let body = crate_registry
+        .download_crate(&query_parameters.name, &query_parameters.version)
+        .await?;
+
+    // unpacking and scanning is cpu bound, keep it off the workers
+    let result = web::block(move || archive::inspect(&body))
+        .await
+        .map_err(|err| match err {
+            BlockingError::Error(err) => err,
+            BlockingError::Canceled => RustKataError::Internal,
+        })?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
@@ -0,0 +1,4 @@
+pub mod archive;
+pub mod endpoints;
+pub mod models;
+pub mod scanner;
@@ -0,0 +1,82 @@
+use crate::interfaces::validation;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+pub struct QueryParams {
+    #[serde(
+        rename = "name",
+        deserialize_with = "validation::deserialize_crate_name"
+    )]
+    pub name: String,
+    #[serde(
+        rename = "version",
+        deserialize_with = "validation::deserialize_exact_version"
+    )]
+    pub version: String,
+    /// Name of the registry the crate is published to. Defaults to the default registry.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub registry: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct InspectResult {
+    #[serde(rename = "manifest")]
+    pub manifest: Manifest,
+    /// Whether the package has a `build.rs` at its root, whether or not it is used.
+    #[serde(rename = "build_rs")]
+    pub build_rs: bool,
+    /// Every file of the package, in the order they were packed.
+    #[serde(rename = "files")]
+    pub files: Vec<File>,
+    /// Sum over all `.rs` files.
+    #[serde(rename = "unsafe_blocks")]
+    pub unsafe_blocks: usize,
+    /// Sum over all `.rs` files.
+    #[serde(rename = "extern_declarations")]
+    pub extern_declarations: usize,
+}
+
+/// The parts of the `Cargo.toml` cargo normalized on publish that tell what a build runs.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Manifest {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "version")]
+    pub version: String,
+    #[serde(rename = "edition")]
+    pub edition: String,
+    #[serde(rename = "rust_version")]
+    pub rust_version: Option<String>,
+    /// Native library the package links to.
+    #[serde(rename = "links")]
+    pub links: Option<String>,
+    /// Path of the build script cargo runs, if any.
+    #[serde(rename = "build")]
+    pub build: Option<String>,
+    #[serde(rename = "proc_macro")]
+    pub proc_macro: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct File {
+    /// Relative to the package root.
+    #[serde(rename = "path")]
+    pub path: String,
+    /// Unpacked, in bytes.
+    #[serde(rename = "size")]
+    pub size: u64,
+    /// Only counted in `.rs` files.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "unsafe_blocks"
+    )]
+    pub unsafe_blocks: Option<usize>,
+    /// Only counted in `.rs` files.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "extern_declarations"
+    )]
+    pub extern_declarations: Option<usize>,
+}
@@ -0,0 +1,287 @@
+//! Lexical scan of Rust source for constructs reviewers look at first. Comments, string
+//! and character literals are skipped, so `unsafe {` in a doc comment does not count. It
+//! does not parse, and macros expanding to `unsafe` are not seen.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counts {
+    /// `unsafe { … }` blocks, not `unsafe fn`, `unsafe impl` or `unsafe trait`.
+    pub unsafe_blocks: usize,
+    /// `extern` blocks and functions, with or without an ABI, not `extern crate`.
+    pub extern_declarations: usize,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal,
+    Punct(char),
+}
+
+pub fn scan(source: &str) -> Counts {
+    let tokens = tokens(source);
+    let mut counts = Counts::default();
+
+    for (index, token) in tokens.iter().enumerate() {
+        let next = |offset: usize| tokens.get(index + offset);
+
+        match token {
+            Token::Ident(ident) if ident == "unsafe" && next(1) == Some(&Token::Punct('{')) => {
+                counts.unsafe_blocks += 1
+            }
+            Token::Ident(ident) if ident == "extern" => {
+                // skip the ABI string, if any
+                let offset = match next(1) {
+                    Some(Token::Literal) => 2,
+                    _ => 1,
+                };
+                match next(offset) {
+                    Some(Token::Punct('{')) => counts.extern_declarations += 1,
+                    Some(Token::Ident(ident)) if ident == "fn" => counts.extern_declarations += 1,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    counts
+}
+
+fn tokens(source: &str) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let at = |index: usize| chars.get(index).copied();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while let Some(c) = at(index) {
+        match c {
+            c if c.is_whitespace() => index += 1,
+            '/' if at(index + 1) == Some('/') => {
+                while !matches!(at(index), None | Some('\n')) {
+                    index += 1;
+                }
+            }
+            '/' if at(index + 1) == Some('*') => {
+                // block comments nest
+                let mut depth = 0;
+                while let Some(c) = at(index) {
+                    match (c, at(index + 1)) {
+                        ('/', Some('*')) => {
+                            depth += 1;
+                            index += 2;
+                        }
+                        ('*', Some('/')) => {
+                            depth -= 1;
+                            index += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => index += 1,
+                    }
+                }
+            }
+            '"' => {
+                index = string_end(&chars, index + 1);
+                tokens.push(Token::Literal);
+            }
+            '\'' => match (at(index + 1), at(index + 2)) {
+                (Some('\\'), _) => {
+                    // past the quote, the backslash and the escaped character
+                    index += 3;
+                    while !matches!(at(index), None | Some('\'')) {
+                        index += 1;
+                    }
+                    index += 1;
+                    tokens.push(Token::Literal);
+                }
+                (Some(_), Some('\'')) => {
+                    index += 3;
+                    tokens.push(Token::Literal);
+                }
+                // a lifetime or label, its name follows as an identifier
+                _ => index += 1,
+            },
+            c if c.is_alphabetic() || c == '_' => {
+                if let Some(end) = prefixed_literal_end(&chars, index) {
+                    index = end;
+                    tokens.push(Token::Literal);
+                    continue;
+                }
+
+                let start = index;
+                while matches!(at(index), Some(c) if c.is_alphanumeric() || c == '_') {
+                    index += 1;
+                }
+                tokens.push(Token::Ident(chars[start..index].iter().collect()));
+            }
+            c if c.is_ascii_digit() => {
+                while matches!(at(index), Some(c) if c.is_alphanumeric() || c == '_' || c == '.') {
+                    index += 1;
+                }
+                tokens.push(Token::Literal);
+            }
+            c => {
+                index += 1;
+                tokens.push(Token::Punct(c));
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Index after the closing quote of a string whose content starts at `index`.
+fn string_end(chars: &[char], mut index: usize) -> usize {
+    while let Some(c) = chars.get(index) {
+        match c {
+            '\\' => index += 2,
+            '"' => return index + 1,
+            _ => index += 1,
+        }
+    }
+    index
+}
+
+/// Index after a byte, raw or byte raw string, or a byte character, starting at `index`.
+fn prefixed_literal_end(chars: &[char], index: usize) -> Option<usize> {
+    let at = |index: usize| chars.get(index).copied();
+    let mut cursor = index;
+
+    if at(cursor) == Some('b') {
+        cursor += 1;
+        match at(cursor) {
+            Some('"') => return Some(string_end(chars, cursor + 1)),
+            Some('\'') => {
+                cursor += 1;
+                if at(cursor) == Some('\\') {
+                    cursor += 1;
+                }
+                cursor += 1;
+                while !matches!(at(cursor), None | Some('\'')) {
+                    cursor += 1;
+                }
+                return Some(cursor + 1);
+            }
+            _ => {}
+        }
+    }
+
+    if at(cursor) != Some('r') {
+        return None;
+    }
+    cursor += 1;
+
+    let mut hashes = 0;
+    while at(cursor) == Some('#') {
+        hashes += 1;
+        cursor += 1;
+    }
+    if at(cursor) != Some('"') {
+        return None;
+    }
+    cursor += 1;
+
+    // raw strings end at a quote followed by as many hashes as they started with
+    while let Some(c) = at(cursor) {
+        cursor += 1;
+        if c == '"' && (0..hashes).all(|offset| at(cursor + offset) == Some('#')) {
+            return Some(cursor + hashes);
+        }
+    }
+    Some(cursor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_unsafe_blocks() {
+        let source = r#"
+            unsafe fn raw(pointer: *const u8) -> u8 {
+                *pointer
+            }
+
+            unsafe impl Send for Handle {}
+
+            fn read(pointer: *const u8) -> u8 {
+                let value = unsafe { raw(pointer) };
+                unsafe {
+                    core::ptr::read(pointer)
+                }
+            }
+        "#;
+
+        assert_eq!(
+            scan(source),
+            Counts {
+                unsafe_blocks: 2,
+                extern_declarations: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_counts_extern_declarations() {
+        let source = r#"
+            extern crate libc;
+
+            extern "C" {
+                fn abs(input: i32) -> i32;
+            }
+
+            extern {
+                fn labs(input: i64) -> i64;
+            }
+
+            unsafe extern "C" {
+                fn strlen(s: *const u8) -> usize;
+            }
+
+            #[no_mangle]
+            pub extern "C" fn exported() {}
+
+            pub extern fn implicit() {}
+        "#;
+
+        assert_eq!(
+            scan(source),
+            Counts {
+                unsafe_blocks: 0,
+                extern_declarations: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_ignores_comments_and_literals() {
+        let source = r####"
+            // unsafe { in a line comment }
+            /* unsafe { /* nested */ extern "C" { } */
+            /// ```
+            /// unsafe { doc() }
+            /// ```
+            fn literals<'a>(value: &'a str) -> &'a str {
+                let _ = "unsafe { \" extern { }";
+                let _ = r#"unsafe { "extern" { }"#;
+                let _ = br##"unsafe {"#"##;
+                let _ = b"extern {";
+                let _ = ('{', '\'', b'{', '\u{7b}');
+                'outer: loop {
+                    break 'outer;
+                }
+                value
+            }
+            fn after() { unsafe { after() } }
+        "####;
+
+        assert_eq!(
+            scan(source),
+            Counts {
+                unsafe_blocks: 1,
+                extern_declarations: 0,
+            }
+        );
+    }
+}
@@ -1,33 +1,242 @@
-use crate::errors::RustKataResult;
+use crate::errors::{RustKataError, RustKataResult};
+use serde::{Deserialize, Serialize};
 
+/// Read api of a registry. Only `get_crate` and `get_crate_dependencies` are needed to
+/// resolve dependency graphs. The rest mirrors the crates.io api for the proxy and fails
+/// with `Unsupported` on backends that have no such data, such as an index.
 #[async_trait::async_trait]
-pub trait CrateRegistry {
+pub trait CrateRegistry: Send + Sync {
     async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response>;
     async fn get_crate_dependencies(
         &self,
         crate_name: &str,
         crate_version: &str,
     ) -> RustKataResult<get_crate_dependencies::Response>;
+
+    async fn get_crate_versions(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_versions::Response> {
+        let response = self.get_crate(crate_name).await?;
+
+        Ok(get_crate_versions::Response {
+            upstream: response.upstream,
+            versions: response.versions,
+            extra: Extra::default(),
+        })
+    }
+
+    /// Users and teams owning the crate, told apart by their `kind`.
+    async fn get_crate_owners(&self, _: &str) -> RustKataResult<get_crate_owners::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn get_crate_downloads(&self, _: &str) -> RustKataResult<get_crate_downloads::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn get_version_downloads(
+        &self,
+        _: &str,
+        _: &str,
+    ) -> RustKataResult<get_crate_downloads::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn get_version_authors(
+        &self,
+        _: &str,
+        _: &str,
+    ) -> RustKataResult<get_version_authors::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    /// Rendered readme of the version, as html.
+    async fn get_version_readme(&self, _: &str, _: &str) -> RustKataResult<String> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn get_reverse_dependencies(
+        &self,
+        _: &str,
+        _: &Page,
+    ) -> RustKataResult<get_reverse_dependencies::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn get_keywords(&self, _: &Page) -> RustKataResult<get_keywords::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn get_keyword(&self, _: &str) -> RustKataResult<get_keyword::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn get_categories(&self, _: &Page) -> RustKataResult<get_categories::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn get_category(&self, _: &str) -> RustKataResult<get_category::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn get_summary(&self) -> RustKataResult<get_summary::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    async fn search(&self, _: &search::Query) -> RustKataResult<search::Response> {
+        Err(RustKataError::Unsupported)
+    }
+
+    /// The `.crate` file of the version, verified against the checksum `get_crate` lists for
+    /// it, so that wrappers caching `get_crate` save asking for the checksum again.
+    async fn download_crate(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<Vec<u8>> {
+        // a file that cannot be verified is not served
+        let checksum = self
+            .get_crate(crate_name)
+            .await?
+            .versions
+            .into_iter()
+            .find(|version| version.num == crate_version)
+            .ok_or(RustKataError::NotFound)?
+            .checksum
+            .ok_or(RustKataError::Integrity)?;
+
+        self.download_verified(crate_name, crate_version, &checksum)
+            .await
+    }
+
+    /// The `.crate` file of the version, verified against `checksum`.
+    async fn download_verified(&self, _: &str, _: &str, _: &str) -> RustKataResult<Vec<u8>> {
+        Err(RustKataError::Unsupported)
+    }
+}
+
+/// Unknown fields captured by the response models.
+pub type Extra = std::collections::HashMap<String, serde_json::Value>;
+
+/// Page of a listing, counted from 1.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Page {
+    pub page: usize,
+    pub per_page: usize,
+}
+
+impl Page {
+    /// Most items crates.io returns in a single page.
+    pub const MAX_PER_PAGE: usize = 100;
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Page {
+            page: 1,
+            per_page: 10,
+        }
+    }
+}
+
+/// Caching headers upstream sent a response with, for the proxy to pass on. Nothing else
+/// upstream sends reaches clients.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Upstream {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub cache_control: Option<String>,
+}
+
+/// Responses that remember the `Upstream` headers they were served with.
+pub trait WithUpstream {
+    fn upstream(&self) -> &Upstream;
+    fn set_upstream(&mut self, upstream: Upstream);
+}
+
+macro_rules! with_upstream {
+    ($($module:ident),*) => {
+        $(
+            impl WithUpstream for $module::Response {
+                fn upstream(&self) -> &Upstream {
+                    &self.upstream
+                }
+
+                fn set_upstream(&mut self, upstream: Upstream) {
+                    self.upstream = upstream;
+                }
+            }
+        )*
+    };
 }
 
+with_upstream!(
+    get_crate,
+    get_crate_dependencies,
+    get_crate_versions,
+    get_crate_owners,
+    get_crate_downloads,
+    get_version_authors,
+    get_reverse_dependencies,
+    get_keywords,
+    get_keyword,
+    get_categories,
+    get_category,
+    get_summary,
+    search
+);
+
+/// Value of a field crates.io may leave out or send as `null`, both read as its default.
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// `meta` of the paginated listings.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct MetaResponse {
+    #[serde(rename = "total")]
+    pub total: i64,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Models of the crates.io api. Fields crates.io leaves out or sends as `null`, such as the
+/// description, repository, docs, badges and avatars, or the lists and links newer responses
+/// can do without, are optional or default to empty,
+/// so such a crate still deserializes. The rest, identity fields above all, are required,
+/// so that an error body is never taken for an empty crate.
 pub mod get_crate {
     use std::collections::HashMap;
 
+    use super::{null_as_default, Extra, Upstream};
     use serde::{Deserialize, Serialize};
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
         #[serde(rename = "crate")]
         pub crate_: CrateResponse,
-        #[serde(rename = "versions")]
+        #[serde(default, deserialize_with = "null_as_default", rename = "versions")]
         pub versions: Vec<VersionResponse>,
-        #[serde(rename = "keywords")]
+        #[serde(default, deserialize_with = "null_as_default", rename = "keywords")]
         pub keywords: Vec<KeywordResponse>,
-        #[serde(rename = "categories")]
+        #[serde(default, deserialize_with = "null_as_default", rename = "categories")]
         pub categories: Vec<CategoryResponse>,
+        /// Backend of a fallback chain the response came from. Not part of the crates.io api.
+        #[serde(skip_serializing_if = "Option::is_none", rename = "served_by")]
+        pub served_by: Option<String>,
+        /// Fields this model does not know about, passed through as they came.
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct CrateResponse {
         #[serde(rename = "id")]
         pub id: String,
@@ -35,13 +244,13 @@ pub mod get_crate {
         pub name: String,
         #[serde(rename = "updated_at")]
         pub updated_at: String,
-        #[serde(rename = "versions")]
+        #[serde(default, deserialize_with = "null_as_default", rename = "versions")]
         pub versions: Vec<i64>,
-        #[serde(rename = "keywords")]
+        #[serde(default, deserialize_with = "null_as_default", rename = "keywords")]
         pub keywords: Vec<String>,
-        #[serde(rename = "categories")]
+        #[serde(default, deserialize_with = "null_as_default", rename = "categories")]
         pub categories: Vec<String>,
-        #[serde(rename = "badges")]
+        #[serde(default, rename = "badges")]
         pub badges: Vec<CrateBadgeResponse>,
         #[serde(rename = "created_at")]
         pub created_at: String,
@@ -54,54 +263,62 @@ pub mod get_crate {
         #[serde(rename = "newest_version")]
         pub newest_version: String,
         #[serde(rename = "description")]
-        pub description: String,
+        pub description: Option<String>,
         #[serde(rename = "homepage")]
         pub homepage: Option<String>,
         #[serde(rename = "documentation")]
         pub documentation: Option<String>,
         #[serde(rename = "repository")]
-        pub repository: String,
+        pub repository: Option<String>,
         #[serde(rename = "links")]
         pub links: CrateLinksResponse,
         #[serde(rename = "exact_match")]
         pub exact_match: bool,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct CrateBadgeResponse {
         #[serde(rename = "badge_type")]
         pub badge_type: String,
         #[serde(rename = "attributes")]
         pub attributes: CrateBadgeAttributesResponse,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct CrateBadgeAttributesResponse {
         #[serde(rename = "service")]
         pub service: Option<String>,
         #[serde(rename = "repository")]
-        pub repository: String,
+        pub repository: Option<String>,
         #[serde(rename = "branch")]
-        pub branch: String,
+        pub branch: Option<String>,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct CrateLinksResponse {
         #[serde(rename = "version_downloads")]
         pub version_downloads: String,
         #[serde(rename = "versions")]
         pub versions: Option<String>,
-        #[serde(rename = "owners")]
-        pub owners: String,
-        #[serde(rename = "owner_team")]
-        pub owner_team: String,
+        #[serde(default, rename = "owners")]
+        pub owners: Option<String>,
+        #[serde(default, rename = "owner_team")]
+        pub owner_team: Option<String>,
         #[serde(rename = "owner_user")]
         pub owner_user: String,
         #[serde(rename = "reverse_dependencies")]
         pub reverse_dependencies: String,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct VersionResponse {
         #[serde(rename = "id")]
         pub id: i64,
@@ -124,18 +341,24 @@ pub mod get_crate {
         #[serde(rename = "yanked")]
         pub yanked: bool,
         #[serde(rename = "license")]
-        pub license: String,
+        pub license: Option<String>,
         #[serde(rename = "links")]
         pub links: VersionLinksResponse,
         #[serde(rename = "crate_size")]
         pub crate_size: Option<i64>,
         #[serde(rename = "published_by")]
         pub published_by: Option<UserResponse>,
-        #[serde(rename = "audit_actions")]
+        #[serde(default, rename = "audit_actions")]
         pub audit_actions: Vec<VersionAuditActionResponse>,
+        #[serde(rename = "checksum")]
+        pub checksum: Option<String>,
+        #[serde(rename = "rust_version")]
+        pub rust_version: Option<String>,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct VersionLinksResponse {
         #[serde(rename = "dependencies")]
         pub dependencies: String,
@@ -143,9 +366,11 @@ pub mod get_crate {
         pub version_downloads: String,
         #[serde(rename = "authors")]
         pub authors: String,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct VersionAuditActionResponse {
         #[serde(rename = "action")]
         pub action: String,
@@ -153,9 +378,11 @@ pub mod get_crate {
         pub user: UserResponse,
         #[serde(rename = "time")]
         pub time: String,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct UserResponse {
         #[serde(rename = "id")]
         pub id: i64,
@@ -164,12 +391,14 @@ pub mod get_crate {
         #[serde(rename = "name")]
         pub name: Option<String>,
         #[serde(rename = "avatar")]
-        pub avatar: String,
+        pub avatar: Option<String>,
         #[serde(rename = "url")]
         pub url: String,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct KeywordResponse {
         #[serde(rename = "id")]
         pub id: String,
@@ -179,9 +408,11 @@ pub mod get_crate {
         pub created_at: String,
         #[serde(rename = "crates_cnt")]
         pub crates_cnt: i64,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct CategoryResponse {
         #[serde(rename = "id")]
         pub id: String,
@@ -195,19 +426,29 @@ pub mod get_crate {
         pub created_at: String,
         #[serde(rename = "crates_cnt")]
         pub crates_cnt: i64,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 }
 
 pub mod get_crate_dependencies {
+    use super::{Extra, Upstream};
     use serde::{Deserialize, Serialize};
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
         #[serde(rename = "dependencies")]
         pub dependencies: Vec<DependencyResponse>,
+        /// Backend of a fallback chain the response came from. Not part of the crates.io api.
+        #[serde(skip_serializing_if = "Option::is_none", rename = "served_by")]
+        pub served_by: Option<String>,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
     pub struct DependencyResponse {
         #[serde(rename = "id")]
         pub id: i64,
@@ -227,7 +468,319 @@ pub mod get_crate_dependencies {
         pub target: Option<String>,
         #[serde(rename = "kind")]
         pub kind: String,
+        #[serde(default, rename = "downloads")]
+        pub downloads: i64,
+        /// Index url of the registry the dependency is published to, when it is not the one
+        /// the dependent is published to.
+        #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+        pub registry: Option<String>,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod get_crate_versions {
+    use super::get_crate::VersionResponse;
+    use super::{Extra, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        #[serde(rename = "versions")]
+        pub versions: Vec<VersionResponse>,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod get_crate_owners {
+    use super::{Extra, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        #[serde(rename = "users")]
+        pub users: Vec<OwnerResponse>,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct OwnerResponse {
+        #[serde(rename = "id")]
+        pub id: i64,
+        #[serde(rename = "login")]
+        pub login: String,
+        /// `user` or `team`.
+        #[serde(rename = "kind")]
+        pub kind: String,
+        #[serde(rename = "name")]
+        pub name: Option<String>,
+        #[serde(rename = "avatar")]
+        pub avatar: Option<String>,
+        #[serde(rename = "url")]
+        pub url: Option<String>,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+/// Daily downloads of a crate's versions, or of a single version.
+pub mod get_crate_downloads {
+    use super::{Extra, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        #[serde(rename = "version_downloads")]
+        pub version_downloads: Vec<VersionDownloadsResponse>,
+        /// Only for a whole crate.
+        #[serde(skip_serializing_if = "Option::is_none", rename = "meta")]
+        pub meta: Option<MetaResponse>,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct VersionDownloadsResponse {
+        #[serde(rename = "version")]
+        pub version: i64,
         #[serde(rename = "downloads")]
         pub downloads: i64,
+        #[serde(rename = "date")]
+        pub date: String,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct MetaResponse {
+        /// Downloads of versions left out of `version_downloads`.
+        #[serde(rename = "extra_downloads")]
+        pub extra_downloads: Vec<ExtraDownloadsResponse>,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct ExtraDownloadsResponse {
+        #[serde(rename = "date")]
+        pub date: String,
+        #[serde(rename = "downloads")]
+        pub downloads: i64,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod get_version_authors {
+    use super::{Extra, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        #[serde(rename = "meta")]
+        pub meta: MetaResponse,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct MetaResponse {
+        /// Authors as listed in the manifest of the version.
+        #[serde(rename = "names")]
+        pub names: Vec<String>,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod get_reverse_dependencies {
+    use super::get_crate::VersionResponse;
+    use super::get_crate_dependencies::DependencyResponse;
+    use super::{Extra, MetaResponse, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        /// Dependencies on the crate, whose `version_id` points into `versions`.
+        #[serde(rename = "dependencies")]
+        pub dependencies: Vec<DependencyResponse>,
+        /// Versions of the dependent crates.
+        #[serde(rename = "versions")]
+        pub versions: Vec<VersionResponse>,
+        #[serde(rename = "meta")]
+        pub meta: MetaResponse,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod get_keywords {
+    use super::get_crate::KeywordResponse;
+    use super::{Extra, MetaResponse, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        #[serde(rename = "keywords")]
+        pub keywords: Vec<KeywordResponse>,
+        #[serde(rename = "meta")]
+        pub meta: MetaResponse,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod get_keyword {
+    use super::get_crate::KeywordResponse;
+    use super::{Extra, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        #[serde(rename = "keyword")]
+        pub keyword: KeywordResponse,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod get_categories {
+    use super::get_crate::CategoryResponse;
+    use super::{Extra, MetaResponse, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        #[serde(rename = "categories")]
+        pub categories: Vec<CategoryResponse>,
+        #[serde(rename = "meta")]
+        pub meta: MetaResponse,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod get_category {
+    use super::get_crate::CategoryResponse;
+    use super::{Extra, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        /// Subcategories and parent categories are kept in the category's `extra`.
+        #[serde(rename = "category")]
+        pub category: CategoryResponse,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod get_summary {
+    use super::get_crate::{CategoryResponse, CrateResponse, KeywordResponse};
+    use super::{Extra, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        #[serde(rename = "num_downloads")]
+        pub num_downloads: i64,
+        #[serde(rename = "num_crates")]
+        pub num_crates: i64,
+        #[serde(rename = "new_crates")]
+        pub new_crates: Vec<CrateResponse>,
+        #[serde(rename = "most_downloaded")]
+        pub most_downloaded: Vec<CrateResponse>,
+        #[serde(rename = "most_recently_downloaded")]
+        pub most_recently_downloaded: Vec<CrateResponse>,
+        #[serde(rename = "just_updated")]
+        pub just_updated: Vec<CrateResponse>,
+        #[serde(rename = "popular_keywords")]
+        pub popular_keywords: Vec<KeywordResponse>,
+        #[serde(rename = "popular_categories")]
+        pub popular_categories: Vec<CategoryResponse>,
+        #[serde(flatten)]
+        pub extra: Extra,
+    }
+}
+
+pub mod search {
+    use super::get_crate::CrateResponse;
+    use super::{Extra, MetaResponse, Page, Upstream};
+    use serde::{Deserialize, Serialize};
+
+    /// Orders crates.io sorts search results by.
+    #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub enum Sort {
+        #[serde(rename = "relevance")]
+        Relevance,
+        #[serde(rename = "alpha")]
+        Alpha,
+        #[serde(rename = "downloads")]
+        Downloads,
+        #[serde(rename = "recent-downloads")]
+        RecentDownloads,
+        #[serde(rename = "recent-updates")]
+        RecentUpdates,
+        #[serde(rename = "new")]
+        New,
+    }
+
+    impl Sort {
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                Sort::Relevance => "relevance",
+                Sort::Alpha => "alpha",
+                Sort::Downloads => "downloads",
+                Sort::RecentDownloads => "recent-downloads",
+                Sort::RecentUpdates => "recent-updates",
+                Sort::New => "new",
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct Query {
+        /// Free text matched against names, descriptions and keywords.
+        pub q: Option<String>,
+        pub sort: Option<Sort>,
+        /// Slug of a category the crates must be in.
+        pub category: Option<String>,
+        /// Keyword the crates must have.
+        pub keyword: Option<String>,
+        pub page: Page,
+    }
+
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    pub struct Response {
+        #[serde(skip)]
+        pub upstream: Upstream,
+        #[serde(rename = "crates")]
+        pub crates: Vec<CrateResponse>,
+        #[serde(rename = "meta")]
+        pub meta: MetaResponse,
+        #[serde(flatten)]
+        pub extra: Extra,
     }
 }
@@ -1,7 +1,10 @@
-use actix_web::error::QueryPayloadError;
-use actix_web::web::QueryConfig;
+use actix_web::error::{JsonPayloadError, QueryPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::web::{JsonConfig, QueryConfig};
 use actix_web::{error, web, HttpRequest, HttpResponse};
 
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct ErrorResponse {
     #[serde(rename = "name")]
@@ -10,6 +13,47 @@ pub struct ErrorResponse {
     pub description: String,
 }
 
+/// RFC 7807 problem details. The `name` and `description` of `ErrorResponse` are kept
+/// as extension members, so clients reading either shape keep working.
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ProblemResponse {
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(rename = "title")]
+    pub title: String,
+    #[serde(rename = "status")]
+    pub status: u16,
+    #[serde(rename = "detail")]
+    pub detail: String,
+    #[serde(flatten)]
+    pub error: ErrorResponse,
+}
+
+/// Responds with an `application/problem+json` body, whose `detail` is `description`.
+pub fn problem(status: StatusCode, code: &str, description: &str) -> HttpResponse {
+    HttpResponse::build(status)
+        .content_type(PROBLEM_JSON)
+        .json(ProblemResponse {
+            type_: "about:blank".to_owned(),
+            title: status.canonical_reason().unwrap_or_default().to_owned(),
+            status: status.as_u16(),
+            detail: description.to_owned(),
+            error: ErrorResponse {
+                code: code.to_owned(),
+                description: description.to_owned(),
+            },
+        })
+}
+
+/// Responds to a request naming a registry that is not configured.
+pub fn unknown_registry(name: &str) -> HttpResponse {
+    problem(
+        StatusCode::NOT_FOUND,
+        "not_found",
+        &format!("registry {} does not exist", name),
+    )
+}
+
 pub fn query_config() -> QueryConfig {
     web::QueryConfig::default().error_handler(|err: QueryPayloadError, _: &HttpRequest| {
         let err_message = match &err {
@@ -18,10 +62,19 @@ pub fn query_config() -> QueryConfig {
 
         error::InternalError::from_response(
             err,
-            HttpResponse::BadRequest().json(ErrorResponse {
-                code: "query".to_owned(),
-                description: err_message,
-            }),
+            problem(StatusCode::BAD_REQUEST, "query", &err_message),
+        )
+        .into()
+    })
+}
+
+pub fn json_config() -> JsonConfig {
+    web::JsonConfig::default().error_handler(|err: JsonPayloadError, _: &HttpRequest| {
+        let err_message = err.to_string();
+
+        error::InternalError::from_response(
+            err,
+            problem(StatusCode::BAD_REQUEST, "json", &err_message),
         )
         .into()
     })
@@ -1,2 +1,3 @@
 pub mod crate_registry;
 pub mod http;
+pub mod validation;
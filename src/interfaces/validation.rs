@@ -0,0 +1,253 @@
+use crate::errors::{RustKataError, RustKataResult};
+use crate::interfaces::crate_registry::Page;
+use serde::{de, Deserialize, Deserializer};
+
+/// Version alias resolved to the highest non-yanked, non-prerelease version of a crate.
+pub const LATEST: &str = "latest";
+
+const MAX_CRATE_NAME_LENGTH: usize = 64;
+
+/// Deepest dependency graph a single request may resolve.
+pub const MAX_DEPTH: usize = 10;
+
+/// Most roots a single batch query may resolve.
+pub const MAX_ROOTS: usize = 100;
+
+/// Checks `name` against the rules crates.io enforces on publish: at most 64 ascii
+/// letters, digits, `-` or `_`, starting with a letter. Anything else, such as `/`, `..`
+/// or `?`, would otherwise end up in upstream paths.
+pub fn crate_name(name: &str) -> RustKataResult<()> {
+    let invalid = |reason: &str| {
+        Err(RustKataError::InvalidInput {
+            description: format!("invalid crate name {:?}: {}", name, reason),
+        })
+    };
+
+    match name.chars().next() {
+        None => return invalid("must not be empty"),
+        Some(first) if !first.is_ascii_alphabetic() => {
+            return invalid("must start with an ascii letter")
+        }
+        _ => {}
+    }
+
+    if name.len() > MAX_CRATE_NAME_LENGTH {
+        return invalid("must be at most 64 characters long");
+    }
+
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return invalid("must only contain ascii letters, digits, `-` or `_`");
+    }
+
+    Ok(())
+}
+
+/// Checks that `version` is an exact semver version.
+pub fn exact_version(version: &str) -> RustKataResult<()> {
+    semver::Version::parse(version)
+        .map(|_| ())
+        .map_err(|err| RustKataError::InvalidInput {
+            description: format!("invalid version {:?}: {}", version, err),
+        })
+}
+
+/// Checks that `version` is an exact semver version or [`LATEST`].
+pub fn version(version: &str) -> RustKataResult<()> {
+    match version {
+        LATEST => Ok(()),
+        _ => exact_version(version),
+    }
+}
+
+/// Checks a keyword or category slug, such as `no-std` or `development-tools::testing`.
+pub fn slug(slug: &str) -> RustKataResult<()> {
+    let valid = slug.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && slug.len() <= MAX_CRATE_NAME_LENGTH
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_+:".contains(c));
+
+    match valid {
+        true => Ok(()),
+        false => Err(RustKataError::InvalidInput {
+            description: format!("invalid slug {:?}", slug),
+        }),
+    }
+}
+
+/// Page of a listing, defaulting like crates.io and capped at its maximum page size.
+pub fn page(page: Option<usize>, per_page: Option<usize>) -> RustKataResult<Page> {
+    let page = Page {
+        page: page.unwrap_or(Page::default().page),
+        per_page: per_page.unwrap_or(Page::default().per_page),
+    };
+
+    if page.page == 0 || page.per_page == 0 || page.per_page > Page::MAX_PER_PAGE {
+        return Err(RustKataError::InvalidInput {
+            description: format!(
+                "invalid page: page must be at least 1 and per_page between 1 and {}",
+                Page::MAX_PER_PAGE
+            ),
+        });
+    }
+
+    Ok(page)
+}
+
+/// Depth of a dependency graph, defaulting to direct dependencies and clamped to
+/// [`MAX_DEPTH`] so that one request cannot walk the whole ecosystem.
+pub fn depth(depth: Option<usize>) -> usize {
+    depth.unwrap_or(1).min(MAX_DEPTH)
+}
+
+/// Checks that a batch query asks for at most [`MAX_ROOTS`] roots.
+pub fn roots(roots: usize) -> RustKataResult<()> {
+    match roots > MAX_ROOTS {
+        true => Err(RustKataError::InvalidInput {
+            description: format!("{} roots asked for, at most {} allowed", roots, MAX_ROOTS),
+        }),
+        false => Ok(()),
+    }
+}
+
+/// Deserializes a crate name, failing on invalid ones so that `query_config` and
+/// `json_config` turn them into a bad request.
+pub fn deserialize_crate_name<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    validated(deserializer, crate_name)
+}
+
+/// Deserializes an exact version or [`LATEST`].
+pub fn deserialize_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    validated(deserializer, version)
+}
+
+/// Deserializes an exact version, aliases not allowed.
+pub fn deserialize_exact_version<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    validated(deserializer, exact_version)
+}
+
+/// Deserializes a keyword or category slug.
+pub fn deserialize_slug<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    validated(deserializer, slug)
+}
+
+pub fn deserialize_optional_slug<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) => {
+            slug(&value).map_err(de::Error::custom)?;
+            Ok(Some(value))
+        }
+        None => Ok(None),
+    }
+}
+
+fn validated<'de, D, F>(deserializer: D, validate: F) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+    F: Fn(&str) -> RustKataResult<()>,
+{
+    let value = String::deserialize(deserializer)?;
+    validate(&value).map_err(de::Error::custom)?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crate_name() {
+        for valid in &["serde", "serde_json", "tokio-util", "a", "Inflector", "x86"] {
+            assert!(crate_name(valid).is_ok(), "{}", valid);
+        }
+
+        let too_long = "a".repeat(65);
+        for invalid in &[
+            "",
+            "1password",
+            "-serde",
+            "_serde",
+            "serde/1.0.0",
+            "..",
+            "../../me",
+            "serde?x=1",
+            "serde%2F",
+            "serde json",
+            "sérde",
+            too_long.as_str(),
+        ] {
+            assert!(
+                matches!(crate_name(invalid), Err(RustKataError::InvalidInput { .. })),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_version() {
+        for valid in &["1.0.0", "0.8.2", "1.0.0-alpha.1", "1.0.0+build.5", "latest"] {
+            assert!(version(valid).is_ok(), "{}", valid);
+        }
+
+        for invalid in &[
+            "", "1", "1.0", "^1.0.0", "1.0.0/..", "../1.0.0", "1.0.0?", "LATEST",
+        ] {
+            assert!(version(invalid).is_err(), "{}", invalid);
+        }
+
+        assert!(exact_version("latest").is_err());
+    }
+
+    #[test]
+    fn test_slug() {
+        for valid in &["no-std", "development-tools::testing", "c++", "rng"] {
+            assert!(slug(valid).is_ok(), "{}", valid);
+        }
+
+        for invalid in &["", "::", "no-std/..", "rng?page=2", "a b"] {
+            assert!(slug(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_page() {
+        assert_eq!(page(None, None).unwrap(), Page::default());
+        assert_eq!(
+            page(Some(3), Some(100)).unwrap(),
+            Page {
+                page: 3,
+                per_page: 100
+            }
+        );
+        assert!(page(Some(0), None).is_err());
+        assert!(page(None, Some(101)).is_err());
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(depth(None), 1);
+        assert_eq!(depth(Some(0)), 0);
+        assert_eq!(depth(Some(3)), 3);
+        assert_eq!(depth(Some(usize::MAX)), MAX_DEPTH);
+    }
+
+    #[test]
+    fn test_roots() {
+        assert!(roots(0).is_ok());
+        assert!(roots(MAX_ROOTS).is_ok());
+        assert!(matches!(
+            roots(MAX_ROOTS + 1),
+            Err(RustKataError::InvalidInput { .. })
+        ));
+    }
+}
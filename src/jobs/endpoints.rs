@@ -0,0 +1,56 @@
+use crate::dependency_graph::models::BatchQueryParams;
+use crate::interfaces::{http, validation};
+use crate::jobs::pool::JobPool;
+use crate::registry::registries::Registries;
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse};
+
+pub fn config(cfg: &mut web::ServiceConfig, host_base_path: &str) {
+    let path = format!("{}/jobs", host_base_path);
+
+    cfg.service(
+        web::scope(&path)
+            .app_data(http::json_config())
+            .route("/dependency-graph", web::post().to(submit_dependency_graph))
+            .route("/{id}", web::get().to(get_job)),
+    );
+}
+
+async fn submit_dependency_graph(
+    web::Json(batch_query_parameters): web::Json<BatchQueryParams>,
+    job_pool: web::Data<JobPool>,
+    registries: web::Data<Registries>,
+) -> HttpResponse {
+    if let Err(err) = validation::roots(batch_query_parameters.roots.len()) {
+        return http::problem(StatusCode::BAD_REQUEST, err.kind(), &err.to_string());
+    }
+
+    for root in &batch_query_parameters.roots {
+        if let Some(registry) = registries.unknown(batch_query_parameters.registry(root)) {
+            return http::unknown_registry(registry);
+        }
+    }
+
+    match job_pool
+        .into_inner()
+        .submit(registries, batch_query_parameters)
+    {
+        Some(response) => HttpResponse::Accepted().json(response),
+        None => http::problem(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "queue_full",
+            "too many jobs are queued, retry later",
+        ),
+    }
+}
+
+async fn get_job(web::Path(id): web::Path<String>, job_pool: web::Data<JobPool>) -> HttpResponse {
+    match job_pool.get(&id) {
+        Some(response) => HttpResponse::Ok().json(response),
+        None => http::problem(
+            StatusCode::NOT_FOUND,
+            "not_found",
+            &format!("job {} does not exist", id),
+        ),
+    }
+}
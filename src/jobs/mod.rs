@@ -0,0 +1,3 @@
+pub mod endpoints;
+pub mod models;
+pub mod pool;
@@ -0,0 +1,40 @@
+use crate::dependency_graph::models::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum JobStatus {
+    #[serde(rename = "queued")]
+    Queued,
+    #[serde(rename = "running")]
+    Running,
+    #[serde(rename = "succeeded")]
+    Succeeded,
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct JobResponse {
+    #[serde(rename = "id")]
+    pub id: String,
+    #[serde(rename = "status")]
+    pub status: JobStatus,
+    #[serde(rename = "progress")]
+    pub progress: JobProgressResponse,
+    #[serde(rename = "created_at")]
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "finished_at")]
+    pub finished_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "error")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "result")]
+    pub result: Option<QueryResult>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct JobProgressResponse {
+    #[serde(rename = "nodes_resolved")]
+    pub nodes_resolved: usize,
+    #[serde(rename = "pending_registry_calls")]
+    pub pending_registry_calls: usize,
+}
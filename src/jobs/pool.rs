@@ -0,0 +1,238 @@
+use crate::dependency_graph::models::{BatchQueryParams, QueryResult};
+use crate::dependency_graph::resolver::{Progress, Resolver};
+use crate::errors::{RustKataError, RustKataResult};
+use crate::interfaces::validation;
+use crate::jobs::models::{JobProgressResponse, JobResponse, JobStatus};
+use crate::observability::metrics;
+use crate::registry::registries::Registries;
+use actix_web::web;
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::future::{AbortHandle, Abortable};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// In-memory job store backed by a bounded worker pool. At most `concurrency` jobs resolve
+/// at once, for at most `run_timeout` each, the rest wait in a queue of at most `max_queued`
+/// jobs. Finished jobs are dropped once they are older than `retention`, queued ones once
+/// they waited that long, which cancels them.
+pub struct JobPool {
+    jobs: Mutex<HashMap<String, Job>>,
+    workers: Arc<Semaphore>,
+    retention: chrono::Duration,
+    max_queued: usize,
+    run_timeout: Option<Duration>,
+}
+
+struct Job {
+    status: JobStatus,
+    progress: Arc<Progress>,
+    created_at: DateTime<Utc>,
+    finished_at: Option<DateTime<Utc>>,
+    error: Option<String>,
+    result: Option<QueryResult>,
+    task: AbortHandle,
+}
+
+impl JobPool {
+    pub const DEFAULT_MAX_QUEUED: usize = 64;
+
+    pub fn new(concurrency: usize, retention: Duration) -> RustKataResult<Self> {
+        if concurrency == 0 {
+            return Err(RustKataError::InvalidInput {
+                description: "job concurrency must be at least 1".to_owned(),
+            });
+        }
+
+        let retention =
+            chrono::Duration::from_std(retention).map_err(|_| RustKataError::InvalidInput {
+                description: format!("job retention of {}s is out of range", retention.as_secs()),
+            })?;
+
+        Ok(JobPool {
+            jobs: Mutex::new(HashMap::new()),
+            workers: Arc::new(Semaphore::new(concurrency)),
+            retention,
+            max_queued: JobPool::DEFAULT_MAX_QUEUED,
+            run_timeout: None,
+        })
+    }
+
+    pub fn max_queued(mut self, max_queued: usize) -> Self {
+        self.max_queued = max_queued;
+        self
+    }
+
+    /// Fails jobs still resolving `run_timeout` after they started.
+    pub fn run_timeout(mut self, run_timeout: Duration) -> Self {
+        self.run_timeout = Some(run_timeout);
+        self
+    }
+
+    /// Queues a job, or returns `None` when the queue is full.
+    pub fn submit(
+        self: Arc<Self>,
+        registries: web::Data<Registries>,
+        batch_query_parameters: BatchQueryParams,
+    ) -> Option<JobResponse> {
+        self.expire();
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let progress = Arc::new(Progress::default());
+        let (task, registration) = AbortHandle::new_pair();
+
+        let response = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let queued = jobs
+                .values()
+                .filter(|job| job.status == JobStatus::Queued)
+                .count();
+            if queued >= self.max_queued {
+                return None;
+            }
+            let job = jobs.entry(id.to_owned()).or_insert(Job {
+                status: JobStatus::Queued,
+                progress: progress.clone(),
+                created_at: Utc::now(),
+                finished_at: None,
+                error: None,
+                result: None,
+                task,
+            });
+            job.response(&id)
+        };
+
+        let run = async move {
+            let _permit = self.workers.clone().acquire_owned().await.unwrap();
+
+            // expired while it waited in the queue
+            if !self.update(&id, |job| job.status = JobStatus::Running) {
+                return;
+            }
+
+            let resolution = async {
+                let mut resolver = Resolver::with_progress(&registries, progress);
+                let depth = validation::depth(batch_query_parameters.depth);
+
+                let mut nodes = Vec::with_capacity(batch_query_parameters.roots.len());
+                for root in &batch_query_parameters.roots {
+                    match resolver
+                        .resolve(
+                            batch_query_parameters.registry(root),
+                            &root.name,
+                            &root.version,
+                            depth,
+                        )
+                        .await
+                    {
+                        Ok(node) => nodes.push(node),
+                        Err(err) => {
+                            metrics::error_count(err.kind());
+                            return Err(format!("{} {}: {}", root.name, root.version, err));
+                        }
+                    }
+                }
+
+                Ok(nodes)
+            };
+
+            let result = match self.run_timeout {
+                Some(run_timeout) => actix_web::rt::time::timeout(run_timeout, resolution)
+                    .await
+                    .unwrap_or_else(|_| {
+                        metrics::error_count(RustKataError::Timeout.kind());
+                        Err(format!("timed out after {}s", run_timeout.as_secs_f64()))
+                    }),
+                None => resolution.await,
+            };
+
+            self.update(&id, |job| {
+                job.finished_at = Some(Utc::now());
+                match result {
+                    Ok(nodes) => {
+                        job.status = JobStatus::Succeeded;
+                        job.result = Some(QueryResult {
+                            data: Some(nodes),
+                            errors: None,
+                        });
+                    }
+                    Err(error) => {
+                        job.status = JobStatus::Failed;
+                        job.error = Some(error);
+                    }
+                }
+            });
+        };
+
+        actix_web::rt::spawn(async move {
+            // aborted when the job is dropped, e.g. expired while queued
+            let _ = Abortable::new(run, registration).await;
+        });
+
+        Some(response)
+    }
+
+    pub fn get(&self, id: &str) -> Option<JobResponse> {
+        self.expire();
+
+        self.jobs
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|job| job.response(id))
+    }
+
+    /// Applies `f` to the job, returning whether it still exists.
+    fn update<F: FnOnce(&mut Job)>(&self, id: &str, f: F) -> bool {
+        match self.jobs.lock().unwrap().get_mut(id) {
+            Some(job) => {
+                f(job);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expire(&self) {
+        let now = Utc::now();
+
+        self.jobs.lock().unwrap().retain(|_, job| {
+            let since = match (job.status, job.finished_at) {
+                (_, Some(finished_at)) => finished_at,
+                (JobStatus::Queued, None) => job.created_at,
+                _ => return true,
+            };
+            !matches!(
+                since.checked_add_signed(self.retention),
+                Some(expires_at) if expires_at <= now
+            )
+        });
+    }
+}
+
+impl Drop for Job {
+    /// Cancels the task of a job that is dropped before it finished.
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Job {
+    fn response(&self, id: &str) -> JobResponse {
+        JobResponse {
+            id: id.to_owned(),
+            status: self.status,
+            progress: JobProgressResponse {
+                nodes_resolved: self.progress.nodes_resolved(),
+                pending_registry_calls: self.progress.pending_registry_calls(),
+            },
+            created_at: self.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            finished_at: self
+                .finished_at
+                .map(|finished_at| finished_at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            error: self.error.to_owned(),
+            result: self.result.to_owned(),
+        }
+    }
+}
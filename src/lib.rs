@@ -5,7 +5,14 @@ extern crate prometheus;
 
 pub mod crates_io;
 pub mod dependency_graph;
+pub mod dump;
 pub mod errors;
+pub mod graphql;
+pub mod index;
+pub mod inspect;
 pub mod interfaces;
+pub mod jobs;
 pub mod observability;
 pub mod proxy;
+pub mod registry;
+pub mod search;
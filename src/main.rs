@@ -1,9 +1,28 @@
-use actix_web::{middleware, App, HttpServer};
+use actix_web::{middleware, web, App, HttpServer};
+use rust_kata_002::crates_io::auth::{self, AuthOptions, Identity, Secret};
+use rust_kata_002::crates_io::circuit_breaker::CircuitBreakerOptions;
+use rust_kata_002::crates_io::rate_limiter::RateLimiterOptions;
+use rust_kata_002::crates_io::retry::RetryOptions;
+use rust_kata_002::crates_io::upstreams::Upstreams;
 use rust_kata_002::crates_io::CratesIoClient;
+use rust_kata_002::dump::registry::DumpRegistry;
+use rust_kata_002::index::local::LocalIndex;
+use rust_kata_002::index::sparse::SparseIndexClient;
 use rust_kata_002::interfaces::crate_registry::CrateRegistry;
-use rust_kata_002::{dependency_graph, observability, proxy};
+use rust_kata_002::jobs::pool::JobPool;
+use rust_kata_002::registry::artifacts::ArtifactStore;
+use rust_kata_002::registry::cached::{CacheOptions, CachedRegistry};
+use rust_kata_002::registry::coalescing::CoalescingRegistry;
+use rust_kata_002::registry::fallback::{FallbackRegistry, Fallthrough};
+use rust_kata_002::registry::registries::{Registries, CRATES_IO_INDEX};
+use rust_kata_002::registry::store::DiskStore;
+use rust_kata_002::{dependency_graph, graphql, inspect, jobs, observability, proxy, search};
 use std::env;
-use std::time::Instant;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -12,8 +31,13 @@ async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
     env_logger::init();
 
-    let crate_registry =
-        env::var("CRATE_REGISTRY_BASE_URL").unwrap_or_else(|_| "https://crates.io".to_owned());
+    // comma separated names of the registries to serve, the first one being the default
+    let crate_registries: Vec<String> = env::var("CRATE_REGISTRIES")
+        .unwrap_or_else(|_| "crates-io".to_owned())
+        .split(',')
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .collect();
 
     let host_address = env::var("HOST_ADDRESS").unwrap_or_else(|_| "0.0.0.0".to_owned());
     let host_port = env::var("HOST_PORT").unwrap_or_else(|_| "8080".to_owned());
@@ -21,18 +45,346 @@ async fn main() -> std::io::Result<()> {
 
     let host_base_path = env::var("HOST_BASE_PATH").unwrap_or_else(|_| "".to_owned());
 
+    let mut job_pool = JobPool::new(
+        env_or("JOB_CONCURRENCY", 4),
+        Duration::from_secs(env_or("JOB_RETENTION_SECONDS", 3600)),
+    )
+    .unwrap_or_else(|err| panic!("JOB_CONCURRENCY or JOB_RETENTION_SECONDS: {}", err))
+    .max_queued(env_or("JOB_MAX_QUEUED", JobPool::DEFAULT_MAX_QUEUED));
+    if let Some(run_timeout) = env_opt("JOB_RUN_TIMEOUT_SECONDS") {
+        job_pool = job_pool.run_timeout(Duration::from_secs(run_timeout));
+    }
+    let job_pool = web::Data::new(job_pool);
+
+    let cache_options = CacheOptions {
+        max_entries: env_or("CACHE_MAX_ENTRIES", CacheOptions::default().max_entries),
+        max_bytes: env_or("CACHE_MAX_BYTES", CacheOptions::default().max_bytes),
+        crate_ttl: env_opt("CACHE_CRATE_TTL_SECONDS")
+            .map(Duration::from_secs)
+            .or(CacheOptions::default().crate_ttl),
+        dependencies_ttl: env_opt("CACHE_DEPENDENCIES_TTL_SECONDS").map(Duration::from_secs),
+    };
+
+    let retry_options = RetryOptions {
+        max_retries: env_or("RETRY_MAX_RETRIES", RetryOptions::default().max_retries),
+        base_delay: env_opt("RETRY_BASE_DELAY_MILLISECONDS")
+            .map(Duration::from_millis)
+            .unwrap_or(RetryOptions::default().base_delay),
+        max_delay: env_opt("RETRY_MAX_DELAY_MILLISECONDS")
+            .map(Duration::from_millis)
+            .unwrap_or(RetryOptions::default().max_delay),
+    };
+
+    let request_timeout = env_opt("REQUEST_TIMEOUT_MILLISECONDS").map(Duration::from_millis);
+
+    let circuit_breaker_options = CircuitBreakerOptions {
+        failure_threshold: env_or(
+            "CIRCUIT_BREAKER_FAILURE_THRESHOLD",
+            CircuitBreakerOptions::default().failure_threshold,
+        ),
+        open_duration: env_opt("CIRCUIT_BREAKER_OPEN_SECONDS")
+            .map(Duration::from_secs)
+            .unwrap_or(CircuitBreakerOptions::default().open_duration),
+    };
+
+    let rate_limiter_options = RateLimiterOptions {
+        requests_per_second: env_or(
+            "RATE_LIMIT_REQUESTS_PER_SECOND",
+            RateLimiterOptions::default().requests_per_second,
+        ),
+        burst: env_or("RATE_LIMIT_BURST", RateLimiterOptions::default().burst),
+        max_queue: env_or(
+            "RATE_LIMIT_MAX_QUEUE",
+            RateLimiterOptions::default().max_queue,
+        ),
+        max_wait: env_opt("RATE_LIMIT_MAX_WAIT_MILLISECONDS")
+            .map(Duration::from_millis)
+            .unwrap_or(RateLimiterOptions::default().max_wait),
+    };
+
+    // shared by all registries, entries are keyed by url and credentials
+    let store = env::var("CRATE_REGISTRY_CACHE_PATH").ok().map(|path| {
+        let store = configured("CRATE_REGISTRY_CACHE_PATH", &path, DiskStore::open(&path));
+        Arc::new(store.max_bytes(env_or(
+            "CRATE_REGISTRY_CACHE_MAX_BYTES",
+            DiskStore::DEFAULT_MAX_BYTES,
+        )))
+    });
+
+    // shared by all registries too, downloads are keyed by checksum
+    let artifacts = env::var("ARTIFACT_CACHE_PATH").ok().map(|path| {
+        Arc::new(configured(
+            "ARTIFACT_CACHE_PATH",
+            &path,
+            ArtifactStore::open(&path),
+        ))
+    });
+
+    // one circuit breaker and rate limiter per upstream, however many registries and
+    // backends talk to it
+    let upstreams = Upstreams::new();
+
+    let mut registries: Option<Registries> = None;
+    for (index, name) in crate_registries.iter().enumerate() {
+        let registry_env = |key: &str| registry_env(name, index == 0, key);
+        let registry_setting = |key: &str| format!("{} {}", name, key);
+
+        // the token is taken from TOKEN, else from the file at TOKEN_PATH, else from the
+        // cargo credentials.toml at CREDENTIALS_PATH
+        let token = match (
+            registry_env("TOKEN"),
+            registry_env("TOKEN_PATH"),
+            registry_env("CREDENTIALS_PATH"),
+        ) {
+            (Some(token), _, _) => Some(Secret::new(&token)),
+            (None, Some(path), _) => Some(configured(
+                &registry_setting("TOKEN_PATH"),
+                &path,
+                auth::token_from_file(&path),
+            )),
+            (None, None, Some(path)) => configured(
+                &registry_setting("CREDENTIALS_PATH"),
+                &path,
+                auth::token_from_credentials(&path, name),
+            ),
+            (None, None, None) => None,
+        };
+        let auth_options = AuthOptions {
+            token,
+            // comma separated PEM files
+            ca_certificates: registry_env("CA_CERT_PATH")
+                .map(|paths| paths.split(',').map(PathBuf::from).collect())
+                .unwrap_or_default(),
+            // PKCS #12 archive
+            identity: registry_env("IDENTITY_PATH").map(|path| Identity {
+                path: PathBuf::from(path),
+                password: Secret::new(&registry_env("IDENTITY_PASSWORD").unwrap_or_default()),
+            }),
+        };
+
+        let auth_paths = [registry_env("CA_CERT_PATH"), registry_env("IDENTITY_PATH")]
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // "api" for the crates.io web api, "sparse" for a sparse index, "local" for an index
+        // on the local filesystem at INDEX_PATH, "dump" for a database dump imported into
+        // DUMP_PATH. A comma separated chain, e.g. "local,sparse,api", asks each backend in
+        // turn, falling through on the errors given by <BACKEND>_FALLTHROUGH.
+        let kinds: Vec<String> = registry_env("BACKEND")
+            .unwrap_or_else(|| "api".to_owned())
+            .split(',')
+            .map(|kind| kind.trim().to_owned())
+            .collect();
+
+        let mut chain = FallbackRegistry::new();
+        let mut single: Option<Box<dyn CrateRegistry>> = None;
+        for kind in &kinds {
+            let kind_env = |key: &str| registry_env(&format!("{}_{}", kind.to_uppercase(), key));
+
+            // every backend talks to its own upstream host, so RATE_LIMIT_* are read per
+            // backend, then per registry. Backends of the same base url share one limiter, so
+            // their settings must agree. A rate of zero turns the limiter off, e.g. for a
+            // registry we host ourselves
+            let rate_limit_env = |key: &str| kind_env(key).or_else(|| registry_env(key));
+            let setting = |key: &str| registry_setting(&format!("{} {}", kind, key));
+            let rate_limiter_options = RateLimiterOptions {
+                requests_per_second: parsed(
+                    &setting("RATE_LIMIT_REQUESTS_PER_SECOND"),
+                    rate_limit_env("RATE_LIMIT_REQUESTS_PER_SECOND"),
+                )
+                .unwrap_or(rate_limiter_options.requests_per_second),
+                burst: parsed(
+                    &setting("RATE_LIMIT_BURST"),
+                    rate_limit_env("RATE_LIMIT_BURST"),
+                )
+                .unwrap_or(rate_limiter_options.burst),
+                max_queue: parsed(
+                    &setting("RATE_LIMIT_MAX_QUEUE"),
+                    rate_limit_env("RATE_LIMIT_MAX_QUEUE"),
+                )
+                .unwrap_or(rate_limiter_options.max_queue),
+                max_wait: parsed(
+                    &setting("RATE_LIMIT_MAX_WAIT_MILLISECONDS"),
+                    rate_limit_env("RATE_LIMIT_MAX_WAIT_MILLISECONDS"),
+                )
+                .map(Duration::from_millis)
+                .unwrap_or(rate_limiter_options.max_wait),
+            };
+            let requests_per_second = rate_limiter_options.requests_per_second;
+            if !requests_per_second.is_finite() || requests_per_second < 0.0 {
+                panic!(
+                    "{}: invalid value {}",
+                    setting("RATE_LIMIT_REQUESTS_PER_SECOND"),
+                    requests_per_second
+                );
+            }
+
+            // BASE_URL is only unambiguous without a chain
+            let base_url = kind_env("BASE_URL")
+                .or_else(|| match kinds.len() {
+                    1 => registry_env("BASE_URL"),
+                    _ => None,
+                })
+                .unwrap_or_else(|| match kind.as_str() {
+                    "sparse" => "https://index.crates.io".to_owned(),
+                    _ => "https://crates.io".to_owned(),
+                });
+
+            // where the index backends download `.crate` files from, when the `dl` of the
+            // index config points away from the index
+            let download_url =
+                kind_env("DOWNLOAD_URL").unwrap_or_else(|| "https://static.crates.io".to_owned());
+
+            let crates_io_client = |base_url: &str| {
+                let mut crates_io_client = configured(
+                    &registry_setting("CA_CERT_PATH or IDENTITY_PATH"),
+                    &auth_paths,
+                    CratesIoClient::with_auth(base_url, &auth_options),
+                )
+                .retry(retry_options.clone())
+                .circuit_breaker(
+                    upstreams.circuit_breaker(base_url, circuit_breaker_options.clone()),
+                );
+                if let Some(request_timeout) = request_timeout {
+                    crates_io_client = crates_io_client.timeout(request_timeout);
+                }
+                if rate_limiter_options.requests_per_second > 0.0 {
+                    crates_io_client = crates_io_client.rate_limiter(
+                        upstreams
+                            .rate_limiter(base_url, rate_limiter_options.clone())
+                            .unwrap_or_else(|err| {
+                                panic!("{} {} RATE_LIMIT_*: {}", name, kind, err)
+                            }),
+                    );
+                }
+                if let Some(store) = &store {
+                    crates_io_client = crates_io_client.store(store.clone());
+                }
+                crates_io_client
+            };
+
+            let backend: Box<dyn CrateRegistry> = match kind.as_str() {
+                "dump" => {
+                    let path = registry_env("DUMP_PATH").unwrap_or_else(|| {
+                        panic!("{}: DUMP_PATH is required by the dump backend", name)
+                    });
+                    Box::new(configured(
+                        &registry_setting("DUMP_PATH"),
+                        &path,
+                        DumpRegistry::open(&path),
+                    ))
+                }
+                "local" => Box::new(
+                    LocalIndex::new(registry_env("INDEX_PATH").unwrap_or_else(|| {
+                        panic!("{}: INDEX_PATH is required by the local backend", name)
+                    }))
+                    .downloads(crates_io_client(&download_url)),
+                ),
+                "sparse" => Box::new(
+                    SparseIndexClient::new(crates_io_client(&base_url))
+                        .downloads(crates_io_client(&download_url)),
+                ),
+                _ => Box::new(crates_io_client(&base_url)),
+            };
+
+            // "not_found", "failure" or "any"
+            let fallthrough = parsed(&setting("FALLTHROUGH"), kind_env("FALLTHROUGH"))
+                .unwrap_or(Fallthrough::Any);
+
+            match kinds.len() {
+                1 => single = Some(backend),
+                _ => chain = chain.backend(kind, backend, fallthrough),
+            }
+        }
+        let backend = single.unwrap_or_else(|| Box::new(chain));
+
+        let mut cached_registry = CachedRegistry::new(
+            name,
+            Box::new(CoalescingRegistry::new(backend)),
+            cache_options.clone(),
+        );
+        if let Some(artifacts) = &artifacts {
+            cached_registry = cached_registry.artifacts(artifacts.clone());
+        }
+        let crate_registry = Box::new(cached_registry);
+        let mut named = match registries {
+            None => Registries::new(name, crate_registry),
+            Some(registries) => registries.registry(name, crate_registry),
+        };
+        if auth_options.token.is_some() || auth_options.identity.is_some() {
+            named = named.private(name);
+        }
+
+        // comma separated urls other registries refer to this one by in their dependencies
+        let index_urls = registry_env("INDEX_URL").unwrap_or_else(|| match name.as_str() {
+            "crates-io" => format!("{},sparse+https://index.crates.io/", CRATES_IO_INDEX),
+            _ => String::new(),
+        });
+        for index_url in index_urls.split(',').filter(|url| !url.is_empty()) {
+            named = named.index(index_url.trim(), name);
+        }
+
+        registries = Some(named);
+    }
+    let registries = web::Data::new(registries.expect("CRATE_REGISTRIES names no registry"));
+
     HttpServer::new(move || {
         App::new()
             .wrap(observability::middleware::metric_middleware())
             .wrap(observability::middleware::logger_middleware())
             .wrap(middleware::NormalizePath::default())
             .data(application_start)
-            .data::<Box<dyn CrateRegistry>>(Box::new(CratesIoClient::new(&crate_registry).unwrap()))
+            .app_data(job_pool.clone())
+            .app_data(registries.clone())
             .configure(observability::endpoints::config)
             .configure(|config| dependency_graph::endpoints::config(config, &host_base_path))
+            .configure(|config| graphql::endpoints::config(config, &host_base_path))
+            .configure(|config| inspect::endpoints::config(config, &host_base_path))
+            .configure(|config| jobs::endpoints::config(config, &host_base_path))
             .configure(|config| proxy::endpoints::config(config, &host_base_path))
+            .configure(|config| search::endpoints::config(config, &host_base_path))
     })
     .bind(host_socket)?
     .run()
     .await
 }
+
+/// Setting `key` of the registry called `name`, from `CRATE_REGISTRY_<NAME>_<KEY>`. The
+/// default registry also falls back to `CRATE_REGISTRY_<KEY>`.
+fn registry_env(name: &str, default: bool, key: &str) -> Option<String> {
+    let prefix = name.to_uppercase().replace('-', "_");
+
+    env::var(format!("CRATE_REGISTRY_{}_{}", prefix, key))
+        .ok()
+        .or_else(|| match default {
+            true => env::var(format!("CRATE_REGISTRY_{}", key)).ok(),
+            false => None,
+        })
+}
+
+fn env_opt<T: FromStr>(key: &str) -> Option<T> {
+    parsed(key, env::var(key).ok())
+}
+
+/// `value` of the setting `key`, if set. A value that does not parse stops the startup
+/// instead of quietly falling back to the default.
+fn parsed<T: FromStr>(key: &str, value: Option<String>) -> Option<T> {
+    value.map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("{}: invalid value {:?}", key, value))
+    })
+}
+
+/// What the setting `key` with `value` opens or loads. A failure stops the startup, naming
+/// the setting, its value and why.
+fn configured<T, E: Display>(key: &str, value: &str, result: Result<T, E>) -> T {
+    result.unwrap_or_else(|err| panic!("{}: {:?}: {}", key, value, err))
+}
+
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    env_opt(key).unwrap_or(default)
+}
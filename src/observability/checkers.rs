@@ -1,3 +1,4 @@
+use crate::crates_io::circuit_breaker::{circuit_states, CircuitState};
 use crate::observability::models::Check;
 use chrono::{DateTime, SecondsFormat, Utc};
 use std::time::Instant;
@@ -17,6 +18,31 @@ pub(crate) fn uptime_checker(now: &DateTime<Utc>, application_start: &Instant) -
     }
 }
 
+pub(crate) fn circuit_breaker_checkers(now: &DateTime<Utc>) -> Vec<Check> {
+    circuit_states()
+        .into_iter()
+        .map(|(base_url, state)| Check {
+            component_id: Some(base_url),
+            component_type: Some("component".to_owned()),
+            observed_value: Some(state.as_str().to_owned()),
+            observed_unit: None,
+            status: Some(
+                match state {
+                    CircuitState::Closed => "pass",
+                    CircuitState::HalfOpen => "warn",
+                    CircuitState::Open => "fail",
+                }
+                .to_owned(),
+            ),
+            affected_endpoints: None,
+            time: Some(now.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            output: None,
+            links: None,
+            additional_keys: None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,4 +1,4 @@
-use crate::observability::checkers::uptime_checker;
+use crate::observability::checkers::{circuit_breaker_checkers, uptime_checker};
 use crate::observability::models::Health;
 use actix_web::{web, HttpResponse};
 use chrono::Utc;
@@ -38,6 +38,11 @@ async fn get_health(application_start: web::Data<Instant>) -> HttpResponse {
         .or_insert(vec![])
         .push(uptime_checker(&now, application_start.get_ref()));
 
+    let circuit_breaker_checks = circuit_breaker_checkers(&now);
+    if !circuit_breaker_checks.is_empty() {
+        checks.insert("registry:circuit".to_owned(), circuit_breaker_checks);
+    }
+
     HttpResponse::Ok()
         .header("content-type", "application/health+json")
         .json(Health::envelope(checks))
@@ -1,25 +1,75 @@
 use actix_web::http::StatusCode;
-use prometheus::{Histogram, HistogramVec, IntCounterVec};
+use prometheus::{Histogram, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec};
 
+const BACKEND: &str = "backend";
 const BASE_URL: &str = "base_url";
 const ENDPOINT: &str = "endpoint";
+const KIND: &str = "kind";
+const OUTCOME: &str = "outcome";
+const REASON: &str = "reason";
+const REGISTRY: &str = "registry";
+const REVALIDATION: &str = "revalidation";
+const STATE: &str = "state";
 const STATUS_CODE: &str = "status_code";
 
 pub fn api_request_duration_seconds(
     base_url: &str,
     endpoint: &str,
     status_code: &StatusCode,
+    revalidation: &str,
 ) -> Histogram {
     lazy_static! {
         static ref METRIC: HistogramVec = register_histogram_vec!(
             "api_request_duration_seconds",
             "api request duration seconds",
-            &[BASE_URL, ENDPOINT, STATUS_CODE]
+            &[BASE_URL, ENDPOINT, STATUS_CODE, REVALIDATION]
         )
         .unwrap();
     }
 
-    METRIC.with_label_values(&[base_url, endpoint, status_code.as_str()])
+    METRIC.with_label_values(&[base_url, endpoint, status_code.as_str(), revalidation])
+}
+
+pub fn api_request_retry_count(base_url: &str, endpoint: &str, reason: &str) {
+    lazy_static! {
+        static ref METRIC: IntCounterVec = register_int_counter_vec!(
+            "api_request_retry_count",
+            "api request retry count",
+            &[BASE_URL, ENDPOINT, REASON]
+        )
+        .unwrap();
+    }
+
+    METRIC
+        .with_label_values(&[base_url, endpoint, reason])
+        .inc()
+}
+
+/// One series per state; the current state is 1, the others 0.
+pub fn circuit_breaker_state(base_url: &str, state: &str) {
+    lazy_static! {
+        static ref METRIC: IntGaugeVec = register_int_gauge_vec!(
+            "circuit_breaker_state",
+            "circuit breaker state",
+            &[BASE_URL, STATE]
+        )
+        .unwrap();
+    }
+
+    for known in &["closed", "open", "half_open"] {
+        METRIC
+            .with_label_values(&[base_url, known])
+            .set((*known == state) as i64)
+    }
+}
+
+pub fn error_count(kind: &str) {
+    lazy_static! {
+        static ref METRIC: IntCounterVec =
+            register_int_counter_vec!("error_count", "error count", &[KIND]).unwrap();
+    }
+
+    METRIC.with_label_values(&[kind]).inc()
 }
 
 pub fn http_request_count(endpoint: &str) {
@@ -59,3 +109,98 @@ pub fn http_response_duration_seconds(endpoint: &str, status_code: &StatusCode)
 
     METRIC.with_label_values(&[endpoint, status_code.as_str()])
 }
+
+pub fn registry_cache_count(registry: &str, endpoint: &str, outcome: &str) {
+    lazy_static! {
+        static ref METRIC: IntCounterVec = register_int_counter_vec!(
+            "registry_cache_count",
+            "registry cache count",
+            &[REGISTRY, ENDPOINT, OUTCOME]
+        )
+        .unwrap();
+    }
+
+    METRIC
+        .with_label_values(&[registry, endpoint, outcome])
+        .inc()
+}
+
+pub fn registry_cache_eviction_count(registry: &str, endpoint: &str, reason: &str) {
+    lazy_static! {
+        static ref METRIC: IntCounterVec = register_int_counter_vec!(
+            "registry_cache_eviction_count",
+            "registry cache eviction count",
+            &[REGISTRY, ENDPOINT, REASON]
+        )
+        .unwrap();
+    }
+
+    METRIC
+        .with_label_values(&[registry, endpoint, reason])
+        .inc()
+}
+
+pub fn registry_coalesced_count(endpoint: &str) {
+    lazy_static! {
+        static ref METRIC: IntCounterVec = register_int_counter_vec!(
+            "registry_coalesced_count",
+            "registry coalesced count",
+            &[ENDPOINT]
+        )
+        .unwrap();
+    }
+
+    METRIC.with_label_values(&[endpoint]).inc()
+}
+
+pub fn registry_fallthrough_count(backend: &str, endpoint: &str, reason: &str) {
+    lazy_static! {
+        static ref METRIC: IntCounterVec = register_int_counter_vec!(
+            "registry_fallthrough_count",
+            "registry fallthrough count",
+            &[BACKEND, ENDPOINT, REASON]
+        )
+        .unwrap();
+    }
+
+    METRIC.with_label_values(&[backend, endpoint, reason]).inc()
+}
+
+pub fn rate_limiter_queue_depth(base_url: &str) -> IntGauge {
+    lazy_static! {
+        static ref METRIC: IntGaugeVec = register_int_gauge_vec!(
+            "rate_limiter_queue_depth",
+            "rate limiter queue depth",
+            &[BASE_URL]
+        )
+        .unwrap();
+    }
+
+    METRIC.with_label_values(&[base_url])
+}
+
+pub fn rate_limiter_rejected_count(base_url: &str, reason: &str) {
+    lazy_static! {
+        static ref METRIC: IntCounterVec = register_int_counter_vec!(
+            "rate_limiter_rejected_count",
+            "rate limiter rejected count",
+            &[BASE_URL, REASON]
+        )
+        .unwrap();
+    }
+
+    METRIC.with_label_values(&[base_url, reason]).inc()
+}
+
+pub fn rate_limiter_wait_seconds(base_url: &str) -> Histogram {
+    lazy_static! {
+        static ref METRIC: HistogramVec = register_histogram_vec!(
+            "rate_limiter_wait_seconds",
+            "rate limiter wait seconds",
+            &[BASE_URL]
+        )
+        .unwrap();
+    }
+
+    METRIC.with_label_values(&[base_url])
+}
@@ -0,0 +1,288 @@
+//! Validators and freshness for proxy responses.
+//!
+//! The `ETag`, `Last-Modified` and `Cache-Control` upstream sent are passed on. Bodies are
+//! re-serialized from the typed registry models, so an upstream `ETag` is passed on as a weak
+//! one. Responses upstream sent no `ETag` with, such as those of an index or a dump, get a
+//! strong one derived from the body. `If-None-Match`, or else `If-Modified-Since`, is
+//! answered with `304 Not Modified`. Upstream statuses and `Retry-After` reach clients
+//! through the errors.
+//!
+//! Responses of registries reached with credentials are `private`, so that shared caches and
+//! the ingress never hand them to other clients.
+
+use crate::interfaces::crate_registry::Upstream;
+use crate::registry::artifacts::sha256;
+use actix_web::http::header::{
+    CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::DateTime;
+
+/// How long clients and shared caches may reuse a response without revalidating it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Freshness {
+    /// Data of a published version, which never changes.
+    Immutable,
+    /// Anything that changes as crates are published, downloaded or yanked.
+    Mutable,
+}
+
+impl Freshness {
+    pub fn cache_control(&self, private: bool) -> &'static str {
+        match (self, private) {
+            (Freshness::Immutable, false) => "public, max-age=31536000, immutable",
+            (Freshness::Immutable, true) => "private, max-age=31536000, immutable",
+            (Freshness::Mutable, false) => "public, max-age=60",
+            (Freshness::Mutable, true) => "private, max-age=60",
+        }
+    }
+}
+
+/// Responds with `body`, or with `304 Not Modified` when the client already has it.
+pub fn respond(
+    request: &HttpRequest,
+    content_type: &str,
+    body: Vec<u8>,
+    freshness: Freshness,
+    private: bool,
+    upstream: &Upstream,
+) -> HttpResponse {
+    let etag = match &upstream.etag {
+        Some(etag) => weak(etag),
+        None => etag(&body),
+    };
+
+    // upstream may not know it is only reachable with credentials
+    let cache_control = match upstream.cache_control.as_deref() {
+        Some(cache_control)
+            if !private
+                || directive(cache_control, "private")
+                || directive(cache_control, "no-store") =>
+        {
+            cache_control
+        }
+        _ => freshness.cache_control(private),
+    };
+
+    let not_modified = match request.headers().contains_key(IF_NONE_MATCH) {
+        true => none_match(request, &etag),
+        false => not_modified_since(request, upstream.last_modified.as_deref()),
+    };
+
+    let mut response = match not_modified {
+        true => HttpResponse::NotModified(),
+        false => HttpResponse::Ok(),
+    };
+    response
+        .set_header(ETAG, etag)
+        .set_header(CACHE_CONTROL, cache_control);
+    if let Some(last_modified) = &upstream.last_modified {
+        response.set_header(LAST_MODIFIED, last_modified.as_str());
+    }
+
+    match not_modified {
+        true => response.finish(),
+        false => response.content_type(content_type).body(body),
+    }
+}
+
+/// Strong validator of `body`, the same on every instance and across deploys.
+fn etag(body: &[u8]) -> String {
+    format!("\"{}\"", sha256(body))
+}
+
+fn weak(etag: &str) -> String {
+    match etag.starts_with("W/") {
+        true => etag.to_owned(),
+        false => format!("W/{}", etag),
+    }
+}
+
+/// Whether `cache_control` has the directive `name`.
+fn directive(cache_control: &str, name: &str) -> bool {
+    cache_control
+        .split(',')
+        .map(|directive| directive.trim().split('=').next().unwrap_or_default())
+        .any(|directive| directive.eq_ignore_ascii_case(name))
+}
+
+/// Whether any entity tag of the request's `If-None-Match` matches `etag`, compared weakly
+/// as RFC 7232 asks for.
+fn none_match(request: &HttpRequest, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+
+    request
+        .headers()
+        .get_all(IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Whether the response, last modified at `last_modified`, is no newer than the request's
+/// `If-Modified-Since`. Dates that do not parse never match.
+fn not_modified_since(request: &HttpRequest, last_modified: Option<&str>) -> bool {
+    let if_modified_since = request
+        .headers()
+        .get(IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value.trim()).ok());
+
+    let last_modified =
+        last_modified.and_then(|value| DateTime::parse_from_rfc2822(value.trim()).ok());
+
+    match (last_modified, if_modified_since) {
+        (Some(last_modified), Some(if_modified_since)) => last_modified <= if_modified_since,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_etag_is_stable() {
+        assert_eq!(etag(b"{}"), etag(b"{}"));
+        assert_ne!(etag(b"{}"), etag(b"[]"));
+        assert_eq!(
+            etag(b"hello"),
+            "\"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824\""
+        );
+        assert!(etag(b"{}").starts_with('"') && etag(b"{}").ends_with('"'));
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = etag(b"{}");
+
+        for (if_none_match, expected) in &[
+            (etag.clone(), StatusCode::NOT_MODIFIED),
+            (format!("W/{}", etag), StatusCode::NOT_MODIFIED),
+            (format!("\"other\", {}", etag), StatusCode::NOT_MODIFIED),
+            ("*".to_owned(), StatusCode::NOT_MODIFIED),
+            ("\"other\"".to_owned(), StatusCode::OK),
+        ] {
+            let request = TestRequest::default()
+                .header(IF_NONE_MATCH, if_none_match.as_str())
+                .to_http_request();
+
+            let response = respond(
+                &request,
+                "application/json",
+                b"{}".to_vec(),
+                Freshness::Mutable,
+                false,
+                &Upstream::default(),
+            );
+
+            assert_eq!(response.status(), *expected, "{}", if_none_match);
+            assert_eq!(response.headers().get(ETAG).unwrap(), etag.as_str());
+            assert_eq!(
+                response.headers().get(CACHE_CONTROL).unwrap(),
+                "public, max-age=60"
+            );
+        }
+    }
+
+    #[test]
+    fn test_private() {
+        let request = TestRequest::default().to_http_request();
+
+        for (freshness, expected) in &[
+            (Freshness::Immutable, "private, max-age=31536000, immutable"),
+            (Freshness::Mutable, "private, max-age=60"),
+        ] {
+            let response = respond(
+                &request,
+                "application/json",
+                b"{}".to_vec(),
+                *freshness,
+                true,
+                &Upstream::default(),
+            );
+
+            assert_eq!(response.headers().get(CACHE_CONTROL).unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_upstream_validators() {
+        let upstream = Upstream {
+            etag: Some("\"a1b2\"".to_owned()),
+            last_modified: Some("Tue, 15 Nov 1994 08:12:31 GMT".to_owned()),
+            cache_control: Some("public, max-age=300".to_owned()),
+        };
+
+        for (name, value, expected) in &[
+            ("if-none-match", "W/\"a1b2\"", StatusCode::NOT_MODIFIED),
+            ("if-none-match", "\"other\"", StatusCode::OK),
+            (
+                "if-modified-since",
+                "Tue, 15 Nov 1994 08:12:31 GMT",
+                StatusCode::NOT_MODIFIED,
+            ),
+            (
+                "if-modified-since",
+                "Mon, 14 Nov 1994 08:12:31 GMT",
+                StatusCode::OK,
+            ),
+            ("if-modified-since", "yesterday", StatusCode::OK),
+        ] {
+            let request = TestRequest::default()
+                .header(*name, *value)
+                .to_http_request();
+
+            let response = respond(
+                &request,
+                "application/json",
+                b"{}".to_vec(),
+                Freshness::Immutable,
+                false,
+                &upstream,
+            );
+
+            assert_eq!(response.status(), *expected, "{}: {}", name, value);
+            assert_eq!(response.headers().get(ETAG).unwrap(), "W/\"a1b2\"");
+            assert_eq!(
+                response.headers().get(LAST_MODIFIED).unwrap(),
+                "Tue, 15 Nov 1994 08:12:31 GMT"
+            );
+            assert_eq!(
+                response.headers().get(CACHE_CONTROL).unwrap(),
+                "public, max-age=300"
+            );
+        }
+    }
+
+    #[test]
+    fn test_upstream_cache_control_of_private_registry() {
+        let request = TestRequest::default().to_http_request();
+
+        for (cache_control, expected) in &[
+            ("public, max-age=300", "private, max-age=60"),
+            ("max-age=300", "private, max-age=60"),
+            ("private, max-age=300", "private, max-age=300"),
+            ("no-store", "no-store"),
+        ] {
+            let upstream = Upstream {
+                cache_control: Some((*cache_control).to_owned()),
+                ..Upstream::default()
+            };
+
+            let response = respond(
+                &request,
+                "application/json",
+                b"{}".to_vec(),
+                Freshness::Mutable,
+                true,
+                &upstream,
+            );
+
+            assert_eq!(response.headers().get(CACHE_CONTROL).unwrap(), *expected);
+        }
+    }
+}
@@ -1,52 +1,468 @@
-use actix_web::{web, HttpResponse};
+use crate::proxy::caching::{self, Freshness};
+use actix_web::http::header::{CACHE_CONTROL, CONTENT_DISPOSITION};
+use actix_web::middleware::DefaultHeaders;
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 
-use crate::interfaces::crate_registry::CrateRegistry;
-use crate::interfaces::http;
+use crate::errors::{RustKataError, RustKataResult};
+use crate::interfaces::crate_registry::{search, CrateRegistry, Upstream, WithUpstream};
+use crate::interfaces::{http, validation};
+use crate::registry::registries::Registries;
 
 pub fn config(cfg: &mut web::ServiceConfig, host_base_path: &str) {
     let path = format!("{}/proxy", host_base_path);
 
     cfg.service(
         web::scope(&path)
+            // errors, which carry no validator, are not to be reused
+            .wrap(DefaultHeaders::new().header(CACHE_CONTROL, "no-store"))
             .app_data(http::query_config())
             .route("/crate", web::get().to(get_crate))
-            .route("/crate_dependencies", web::get().to(get_crate_dependency)),
+            .route("/crate_dependencies", web::get().to(get_crate_dependency))
+            .route("/crate_versions", web::get().to(get_crate_versions))
+            .route("/crate_owners", web::get().to(get_crate_owners))
+            .route("/crate_downloads", web::get().to(get_crate_downloads))
+            .route("/version_downloads", web::get().to(get_version_downloads))
+            .route("/version_authors", web::get().to(get_version_authors))
+            .route("/version_readme", web::get().to(get_version_readme))
+            .route(
+                "/reverse_dependencies",
+                web::get().to(get_reverse_dependencies),
+            )
+            .route("/keywords", web::get().to(get_keywords))
+            .route("/keyword", web::get().to(get_keyword))
+            .route("/categories", web::get().to(get_categories))
+            .route("/category", web::get().to(get_category))
+            .route("/summary", web::get().to(get_summary))
+            .route("/search", web::get().to(search))
+            .route("/download/{name}/{version}", web::get().to(download_crate)),
     );
 }
 
 async fn get_crate(
     web::Query(query_parameters): web::Query<GetCrateQueryParams>,
-    crates_io_client: web::Data<Box<dyn CrateRegistry>>,
-) -> HttpResponse {
-    let response = crates_io_client
-        .get_crate(&query_parameters.name)
-        .await
-        .unwrap();
-    HttpResponse::Ok().json(response)
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_crate(&query_parameters.name),
+    )
+    .await
 }
 
 async fn get_crate_dependency(
     web::Query(query_parameters): web::Query<GetCrateDependenciesQueryParams>,
-    crates_io_client: web::Data<Box<dyn CrateRegistry>>,
-) -> HttpResponse {
-    let response = crates_io_client
-        .get_crate_dependencies(&query_parameters.name, &query_parameters.version)
-        .await
-        .unwrap();
-    HttpResponse::Ok().json(response)
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Immutable,
+        |crate_registry| {
+            crate_registry.get_crate_dependencies(&query_parameters.name, &query_parameters.version)
+        },
+    )
+    .await
+}
+
+async fn get_crate_versions(
+    web::Query(query_parameters): web::Query<GetCrateQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_crate_versions(&query_parameters.name),
+    )
+    .await
+}
+
+async fn get_crate_owners(
+    web::Query(query_parameters): web::Query<GetCrateQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_crate_owners(&query_parameters.name),
+    )
+    .await
+}
+
+async fn get_crate_downloads(
+    web::Query(query_parameters): web::Query<GetCrateQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_crate_downloads(&query_parameters.name),
+    )
+    .await
+}
+
+async fn get_version_downloads(
+    web::Query(query_parameters): web::Query<GetCrateDependenciesQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| {
+            crate_registry.get_version_downloads(&query_parameters.name, &query_parameters.version)
+        },
+    )
+    .await
+}
+
+async fn get_version_authors(
+    web::Query(query_parameters): web::Query<GetCrateDependenciesQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Immutable,
+        |crate_registry| {
+            crate_registry.get_version_authors(&query_parameters.name, &query_parameters.version)
+        },
+    )
+    .await
+}
+
+async fn get_version_readme(
+    web::Query(query_parameters): web::Query<GetCrateDependenciesQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    let crate_registry = match registries.get(query_parameters.registry.as_deref()) {
+        Some(crate_registry) => crate_registry,
+        None => {
+            return Ok(http::unknown_registry(
+                query_parameters.registry.as_deref().unwrap_or_default(),
+            ))
+        }
+    };
+
+    let readme = crate_registry
+        .get_version_readme(&query_parameters.name, &query_parameters.version)
+        .await?;
+    Ok(caching::respond(
+        &request,
+        "text/html; charset=utf-8",
+        readme.into_bytes(),
+        Freshness::Immutable,
+        registries.is_private(query_parameters.registry.as_deref()),
+        &Upstream::default(),
+    ))
+}
+
+async fn get_reverse_dependencies(
+    web::Query(query_parameters): web::Query<GetReverseDependenciesQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    let page = validation::page(query_parameters.page, query_parameters.per_page)?;
+
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_reverse_dependencies(&query_parameters.name, &page),
+    )
+    .await
+}
+
+async fn get_keywords(
+    web::Query(query_parameters): web::Query<PageQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    let page = validation::page(query_parameters.page, query_parameters.per_page)?;
+
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_keywords(&page),
+    )
+    .await
+}
+
+async fn get_keyword(
+    web::Query(query_parameters): web::Query<GetKeywordQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_keyword(&query_parameters.id),
+    )
+    .await
+}
+
+async fn get_categories(
+    web::Query(query_parameters): web::Query<PageQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    let page = validation::page(query_parameters.page, query_parameters.per_page)?;
+
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_categories(&page),
+    )
+    .await
+}
+
+async fn get_category(
+    web::Query(query_parameters): web::Query<GetCategoryQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_category(&query_parameters.slug),
+    )
+    .await
+}
+
+async fn get_summary(
+    web::Query(query_parameters): web::Query<RegistryQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.get_summary(),
+    )
+    .await
+}
+
+async fn search(
+    web::Query(query_parameters): web::Query<SearchQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    let query = search::Query {
+        q: query_parameters.q,
+        sort: query_parameters.sort,
+        category: query_parameters.category,
+        keyword: query_parameters.keyword,
+        page: validation::page(query_parameters.page, query_parameters.per_page)?,
+    };
+
+    json(
+        &request,
+        &registries,
+        &query_parameters.registry,
+        Freshness::Mutable,
+        |crate_registry| crate_registry.search(&query),
+    )
+    .await
+}
+
+async fn download_crate(
+    web::Path((name, version)): web::Path<(String, String)>,
+    web::Query(query_parameters): web::Query<RegistryQueryParams>,
+    registries: web::Data<Registries>,
+    request: HttpRequest,
+) -> RustKataResult<HttpResponse> {
+    validation::crate_name(&name)?;
+    validation::exact_version(&version)?;
+
+    let crate_registry = match registries.get(query_parameters.registry.as_deref()) {
+        Some(crate_registry) => crate_registry,
+        None => {
+            return Ok(http::unknown_registry(
+                query_parameters.registry.as_deref().unwrap_or_default(),
+            ))
+        }
+    };
+
+    let body = crate_registry.download_crate(&name, &version).await?;
+
+    let mut response = caching::respond(
+        &request,
+        "application/gzip",
+        body,
+        Freshness::Immutable,
+        registries.is_private(query_parameters.registry.as_deref()),
+        &Upstream::default(),
+    );
+    // name and version are validated, nothing in them needs quoting
+    if let Ok(value) = format!("attachment; filename=\"{}-{}.crate\"", name, version).parse() {
+        response.headers_mut().insert(CONTENT_DISPOSITION, value);
+    }
+    Ok(response)
+}
+
+/// Responds with what `call` gets from the registry named `registry`, as json.
+async fn json<'a, T, F>(
+    request: &HttpRequest,
+    registries: &'a Registries,
+    registry: &Option<String>,
+    freshness: Freshness,
+    call: F,
+) -> RustKataResult<HttpResponse>
+where
+    T: Serialize + WithUpstream,
+    F: FnOnce(&'a dyn CrateRegistry) -> BoxFuture<'a, RustKataResult<T>>,
+{
+    let crate_registry = match registries.get(registry.as_deref()) {
+        Some(crate_registry) => crate_registry,
+        None => {
+            return Ok(http::unknown_registry(
+                registry.as_deref().unwrap_or_default(),
+            ))
+        }
+    };
+
+    let response = call(crate_registry).await?;
+    let body = serde_json::to_vec(&response).map_err(|_| RustKataError::Internal)?;
+    Ok(caching::respond(
+        request,
+        "application/json",
+        body,
+        freshness,
+        registries.is_private(registry.as_deref()),
+        response.upstream(),
+    ))
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct RegistryQueryParams {
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub(crate) registry: Option<String>,
 }
 
 #[derive(Deserialize, Serialize)]
 pub(crate) struct GetCrateQueryParams {
-    #[serde(rename = "name")]
+    #[serde(
+        rename = "name",
+        deserialize_with = "validation::deserialize_crate_name"
+    )]
     pub(crate) name: String,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub(crate) registry: Option<String>,
 }
 
 #[derive(Deserialize, Serialize)]
 pub(crate) struct GetCrateDependenciesQueryParams {
-    #[serde(rename = "name")]
+    #[serde(
+        rename = "name",
+        deserialize_with = "validation::deserialize_crate_name"
+    )]
     pub(crate) name: String,
-    #[serde(rename = "version")]
+    #[serde(
+        rename = "version",
+        deserialize_with = "validation::deserialize_exact_version"
+    )]
     pub(crate) version: String,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub(crate) registry: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct PageQueryParams {
+    #[serde(skip_serializing_if = "Option::is_none", rename = "page")]
+    pub(crate) page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "per_page")]
+    pub(crate) per_page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub(crate) registry: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct GetReverseDependenciesQueryParams {
+    #[serde(
+        rename = "name",
+        deserialize_with = "validation::deserialize_crate_name"
+    )]
+    pub(crate) name: String,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "page")]
+    pub(crate) page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "per_page")]
+    pub(crate) per_page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub(crate) registry: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct GetKeywordQueryParams {
+    #[serde(rename = "id", deserialize_with = "validation::deserialize_slug")]
+    pub(crate) id: String,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub(crate) registry: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct GetCategoryQueryParams {
+    #[serde(rename = "slug", deserialize_with = "validation::deserialize_slug")]
+    pub(crate) slug: String,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub(crate) registry: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct SearchQueryParams {
+    #[serde(skip_serializing_if = "Option::is_none", rename = "q")]
+    pub(crate) q: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "sort")]
+    pub(crate) sort: Option<search::Sort>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "category",
+        deserialize_with = "validation::deserialize_optional_slug"
+    )]
+    pub(crate) category: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "keyword",
+        deserialize_with = "validation::deserialize_optional_slug"
+    )]
+    pub(crate) keyword: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "page")]
+    pub(crate) page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "per_page")]
+    pub(crate) per_page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub(crate) registry: Option<String>,
 }
@@ -1 +1,2 @@
+pub mod caching;
 pub mod endpoints;
@@ -0,0 +1,171 @@
+use crate::errors::{RustKataError, RustKataResult};
+use actix_web::error::BlockingError;
+use actix_web::web;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Verified `.crate` files on disk, addressed by their SHA-256. Identical files published
+/// under several names or registries are kept once, and a file is never trusted unless its
+/// content still hashes to its address.
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn open<P: AsRef<Path>>(root: P) -> RustKataResult<Self> {
+        fs::create_dir_all(root.as_ref()).map_err(|_| RustKataError::Internal)?;
+
+        Ok(ArtifactStore {
+            root: root.as_ref().to_path_buf(),
+        })
+    }
+
+    /// The artifact with `checksum`, unless it is missing or corrupt on disk. Reading and
+    /// hashing run on the blocking thread pool.
+    pub async fn get(&self, checksum: &str) -> Option<Vec<u8>> {
+        let path = self.path(checksum)?;
+        let checksum = checksum.to_owned();
+
+        web::block(move || {
+            let body = fs::read(&path)?;
+
+            if sha256(&body) != checksum {
+                log::warn!("removing corrupt artifact {}", path.display());
+                fs::remove_file(&path)?;
+                return Ok(None);
+            }
+
+            Ok::<_, io::Error>(Some(body))
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Keeps `body` under `checksum`, which the caller has verified it against. Writing runs
+    /// on the blocking thread pool.
+    pub async fn put(&self, checksum: &str, body: &[u8]) {
+        let path = match self.path(checksum) {
+            Some(path) => path,
+            None => return,
+        };
+        let body = body.to_vec();
+
+        // written aside and renamed, so readers never see a partial file
+        let temporary = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        let result = web::block(move || {
+            let result = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&temporary, body))
+                .and_then(|_| fs::rename(&temporary, &path));
+
+            if result.is_err() {
+                let _ = fs::remove_file(&temporary);
+            }
+
+            result.map_err(|err| format!("{}: {}", path.display(), err))
+        })
+        .await;
+
+        if let Err(err) = result {
+            log::warn!("failed to write artifact {}", err);
+        }
+    }
+
+    /// `<root>/ab/abcdef….crate`, or `None` for anything but a lowercase hex SHA-256.
+    fn path(&self, checksum: &str) -> Option<PathBuf> {
+        if checksum.len() != 64 || !checksum.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')) {
+            return None;
+        }
+
+        Some(
+            self.root
+                .join(&checksum[..2])
+                .join(format!("{}.crate", checksum)),
+        )
+    }
+}
+
+/// Lowercase hex SHA-256 of `body`, as registries publish checksums.
+pub fn sha256(body: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(body))
+}
+
+/// Hands `body` back, or fails with `Integrity` unless it hashes to `checksum`. Hashing
+/// runs on the blocking thread pool.
+pub async fn verify(body: Vec<u8>, checksum: &str) -> RustKataResult<Vec<u8>> {
+    let checksum = checksum.to_ascii_lowercase();
+
+    web::block(move || match sha256(&body) == checksum {
+        true => Ok(body),
+        false => Err(RustKataError::Integrity),
+    })
+    .await
+    .map_err(|err| match err {
+        BlockingError::Error(err) => err,
+        BlockingError::Canceled => RustKataError::Internal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn store() -> ArtifactStore {
+        ArtifactStore::open(std::env::temp_dir().join(uuid::Uuid::new_v4().to_string())).unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_sha256() {
+        assert_eq!(sha256(b"hello"), HELLO);
+        assert_eq!(verify(b"hello".to_vec(), HELLO).await.unwrap(), b"hello");
+        assert!(verify(b"hello".to_vec(), &HELLO.to_uppercase())
+            .await
+            .is_ok());
+        assert!(matches!(
+            verify(b"hellO".to_vec(), HELLO).await,
+            Err(RustKataError::Integrity)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_put_get() {
+        let store = store();
+
+        assert_eq!(store.get(HELLO).await, None);
+
+        store.put(HELLO, b"hello").await;
+
+        assert_eq!(store.get(HELLO).await, Some(b"hello".to_vec()));
+        assert!(store
+            .root
+            .join("2c")
+            .join(format!("{}.crate", HELLO))
+            .exists());
+    }
+
+    #[actix_rt::test]
+    async fn test_corrupt_artifact_removed() {
+        let store = store();
+
+        store.put(HELLO, b"tampered").await;
+
+        assert_eq!(store.get(HELLO).await, None);
+        assert!(!store.path(HELLO).unwrap().exists());
+    }
+
+    #[actix_rt::test]
+    async fn test_rejects_paths_that_are_not_checksums() {
+        let store = store();
+
+        assert_eq!(store.path("../../etc/passwd"), None);
+        assert_eq!(store.path(&HELLO.to_uppercase()), None);
+        store.put("../escape", b"hello").await;
+        assert_eq!(store.get("../escape").await, None);
+    }
+}
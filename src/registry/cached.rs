@@ -0,0 +1,599 @@
+use crate::errors::RustKataResult;
+use crate::interfaces::crate_registry::{
+    get_categories, get_category, get_crate, get_crate_dependencies, get_crate_downloads,
+    get_crate_owners, get_crate_versions, get_keyword, get_keywords, get_reverse_dependencies,
+    get_summary, get_version_authors, search, CrateRegistry, Page,
+};
+use crate::observability::metrics;
+use crate::registry::artifacts::{self, ArtifactStore};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug)]
+pub struct CacheOptions {
+    /// Entries kept before the least recently used are evicted.
+    pub max_entries: usize,
+    /// Estimated serialized size of all entries kept before the least recently used are
+    /// evicted.
+    pub max_bytes: usize,
+    /// How long `get_crate` metadata is served before it is fetched again.
+    pub crate_ttl: Option<Duration>,
+    /// How long dependencies of a published version are served. They are immutable, so by
+    /// default they never expire.
+    pub dependencies_ttl: Option<Duration>,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        CacheOptions {
+            max_entries: 10_000,
+            max_bytes: 256 * 1024 * 1024,
+            crate_ttl: Some(Duration::from_secs(300)),
+            dependencies_ttl: None,
+        }
+    }
+}
+
+/// In-memory LRU cache in front of any other registry. Only crates and dependencies are
+/// cached, the rest of the api is passed through. Downloads can be kept in an artifact
+/// store on disk.
+pub struct CachedRegistry {
+    /// Name of the registry, which labels the cache metrics.
+    name: String,
+    crate_registry: Box<dyn CrateRegistry>,
+    options: CacheOptions,
+    cache: Mutex<Lru>,
+    artifacts: Option<Arc<ArtifactStore>>,
+}
+
+impl CachedRegistry {
+    pub fn new(name: &str, crate_registry: Box<dyn CrateRegistry>, options: CacheOptions) -> Self {
+        CachedRegistry {
+            name: name.to_owned(),
+            crate_registry,
+            options,
+            cache: Mutex::new(Lru::default()),
+            artifacts: None,
+        }
+    }
+
+    /// Keeps downloaded `.crate` files, found again by the checksum of their version.
+    pub fn artifacts(mut self, artifacts: Arc<ArtifactStore>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+
+    fn get(&self, key: &Key) -> Option<Value> {
+        let value = self.cache.lock().unwrap().get(&self.name, key);
+
+        metrics::registry_cache_count(
+            &self.name,
+            key.method(),
+            if value.is_some() { "hit" } else { "miss" },
+        );
+
+        value
+    }
+
+    fn insert(&self, key: Key, value: Value, ttl: Option<Duration>) {
+        let bytes = value.bytes();
+
+        self.cache.lock().unwrap().insert(
+            &self.name,
+            key,
+            value,
+            bytes,
+            ttl.map(|ttl| Instant::now() + ttl),
+            &self.options,
+        );
+    }
+}
+
+#[async_trait]
+impl CrateRegistry for CachedRegistry {
+    async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response> {
+        let key = Key::Crate(crate_name.to_owned());
+
+        if let Some(Value::Crate(response)) = self.get(&key) {
+            return Ok(response.as_ref().clone());
+        }
+
+        let response = self.crate_registry.get_crate(crate_name).await?;
+
+        self.insert(
+            key,
+            Value::Crate(Arc::new(response.clone())),
+            self.options.crate_ttl,
+        );
+
+        Ok(response)
+    }
+
+    async fn get_crate_dependencies(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_dependencies::Response> {
+        let key = Key::Dependencies(crate_name.to_owned(), crate_version.to_owned());
+
+        if let Some(Value::Dependencies(response)) = self.get(&key) {
+            return Ok(response.as_ref().clone());
+        }
+
+        let response = self
+            .crate_registry
+            .get_crate_dependencies(crate_name, crate_version)
+            .await?;
+
+        self.insert(
+            key,
+            Value::Dependencies(Arc::new(response.clone())),
+            self.options.dependencies_ttl,
+        );
+
+        Ok(response)
+    }
+
+    async fn get_crate_versions(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_versions::Response> {
+        self.crate_registry.get_crate_versions(crate_name).await
+    }
+
+    async fn get_crate_owners(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_owners::Response> {
+        self.crate_registry.get_crate_owners(crate_name).await
+    }
+
+    async fn get_crate_downloads(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_downloads::Response> {
+        self.crate_registry.get_crate_downloads(crate_name).await
+    }
+
+    async fn get_version_downloads(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_downloads::Response> {
+        self.crate_registry
+            .get_version_downloads(crate_name, crate_version)
+            .await
+    }
+
+    async fn get_version_authors(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_version_authors::Response> {
+        self.crate_registry
+            .get_version_authors(crate_name, crate_version)
+            .await
+    }
+
+    async fn get_version_readme(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<String> {
+        self.crate_registry
+            .get_version_readme(crate_name, crate_version)
+            .await
+    }
+
+    async fn get_reverse_dependencies(
+        &self,
+        crate_name: &str,
+        page: &Page,
+    ) -> RustKataResult<get_reverse_dependencies::Response> {
+        self.crate_registry
+            .get_reverse_dependencies(crate_name, page)
+            .await
+    }
+
+    async fn get_keywords(&self, page: &Page) -> RustKataResult<get_keywords::Response> {
+        self.crate_registry.get_keywords(page).await
+    }
+
+    async fn get_keyword(&self, keyword: &str) -> RustKataResult<get_keyword::Response> {
+        self.crate_registry.get_keyword(keyword).await
+    }
+
+    async fn get_categories(&self, page: &Page) -> RustKataResult<get_categories::Response> {
+        self.crate_registry.get_categories(page).await
+    }
+
+    async fn get_category(&self, category: &str) -> RustKataResult<get_category::Response> {
+        self.crate_registry.get_category(category).await
+    }
+
+    async fn get_summary(&self) -> RustKataResult<get_summary::Response> {
+        self.crate_registry.get_summary().await
+    }
+
+    async fn search(&self, query: &search::Query) -> RustKataResult<search::Response> {
+        self.crate_registry.search(query).await
+    }
+
+    // `download_crate` takes the checksum from the cached `get_crate`
+    async fn download_verified(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+        checksum: &str,
+    ) -> RustKataResult<Vec<u8>> {
+        let artifacts = match &self.artifacts {
+            Some(artifacts) => artifacts,
+            None => {
+                return self
+                    .crate_registry
+                    .download_verified(crate_name, crate_version, checksum)
+                    .await
+            }
+        };
+
+        let checksum = checksum.to_ascii_lowercase();
+
+        if let Some(body) = artifacts.get(&checksum).await {
+            metrics::registry_cache_count(&self.name, "download_crate", "hit");
+            return Ok(body);
+        }
+        metrics::registry_cache_count(&self.name, "download_crate", "miss");
+
+        let body = self
+            .crate_registry
+            .download_verified(crate_name, crate_version, &checksum)
+            .await?;
+
+        // never keep a file under a checksum it does not match
+        let body = artifacts::verify(body, &checksum).await?;
+        artifacts.put(&checksum, &body).await;
+
+        Ok(body)
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Key {
+    Crate(String),
+    Dependencies(String, String),
+}
+
+impl Key {
+    fn method(&self) -> &'static str {
+        match self {
+            Key::Crate(_) => "get_crate",
+            Key::Dependencies(_, _) => "get_crate_dependencies",
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Value {
+    Crate(Arc<get_crate::Response>),
+    Dependencies(Arc<get_crate_dependencies::Response>),
+}
+
+/// Typical serialized sizes, measured on crates.io responses, that entries are sized by
+/// without serializing them.
+const CRATE_BYTES: usize = 2048;
+const VERSION_BYTES: usize = 1024;
+const DEPENDENCY_BYTES: usize = 192;
+
+impl Value {
+    fn bytes(&self) -> usize {
+        match self {
+            Value::Crate(response) => CRATE_BYTES + response.versions.len() * VERSION_BYTES,
+            Value::Dependencies(response) => (1 + response.dependencies.len()) * DEPENDENCY_BYTES,
+        }
+    }
+}
+
+struct Entry {
+    value: Value,
+    bytes: usize,
+    expires_at: Option<Instant>,
+    used: u64,
+}
+
+/// Recency is tracked by a tick stamped on every use; `order` maps ticks back to keys so
+/// the least recently used entry is always the first one.
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<Key, Entry>,
+    order: BTreeMap<u64, Key>,
+    bytes: usize,
+    tick: u64,
+}
+
+impl Lru {
+    fn get(&mut self, registry: &str, key: &Key) -> Option<Value> {
+        let expired = match self.entries.get(key) {
+            Some(entry) => {
+                matches!(entry.expires_at, Some(expires_at) if expires_at <= Instant::now())
+            }
+            None => return None,
+        };
+
+        if expired {
+            self.remove(key);
+            metrics::registry_cache_eviction_count(registry, key.method(), "expired");
+            return None;
+        }
+
+        self.tick += 1;
+        let entry = self.entries.get_mut(key).unwrap();
+        self.order.remove(&entry.used);
+        self.order.insert(self.tick, key.clone());
+        entry.used = self.tick;
+
+        Some(entry.value.clone())
+    }
+
+    fn insert(
+        &mut self,
+        registry: &str,
+        key: Key,
+        value: Value,
+        bytes: usize,
+        expires_at: Option<Instant>,
+        options: &CacheOptions,
+    ) {
+        self.remove(&key);
+
+        if bytes > options.max_bytes || options.max_entries == 0 {
+            return;
+        }
+
+        self.tick += 1;
+        self.order.insert(self.tick, key.clone());
+        self.bytes += bytes;
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                bytes,
+                expires_at,
+                used: self.tick,
+            },
+        );
+
+        while self.entries.len() > options.max_entries || self.bytes > options.max_bytes {
+            let oldest = match self.order.keys().next() {
+                Some(used) => self.order[used].clone(),
+                None => break,
+            };
+            self.remove(&oldest);
+            metrics::registry_cache_eviction_count(registry, oldest.method(), "capacity");
+        }
+    }
+
+    fn remove(&mut self, key: &Key) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.used);
+            self.bytes -= entry.bytes;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::RustKataError;
+    use crate::registry::mock::MockBar;
+    use mockall::predicate::*;
+
+    #[actix_rt::test]
+    async fn test_get_crate_dependencies_cached() {
+        let mut mock = MockBar::new();
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Ok(dependencies()));
+
+        let registry = CachedRegistry::new("crates-io", Box::new(mock), CacheOptions::default());
+
+        for _ in 0..3 {
+            let response = registry
+                .get_crate_dependencies("rand", "0.8.2")
+                .await
+                .unwrap();
+            assert_eq!(response.dependencies.len(), 10);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_get_crate_expires() {
+        let mut mock = MockBar::new();
+        mock.expect_get_crate()
+            .with(eq("rand"))
+            .times(2)
+            .returning(|_| Ok(crate_()));
+
+        let registry = CachedRegistry::new(
+            "crates-io",
+            Box::new(mock),
+            CacheOptions {
+                crate_ttl: Some(Duration::from_secs(0)),
+                ..CacheOptions::default()
+            },
+        );
+
+        registry.get_crate("rand").await.unwrap();
+        registry.get_crate("rand").await.unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_least_recently_used_evicted() {
+        let mut mock = MockBar::new();
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.1"))
+            .times(2)
+            .returning(|_, _| Ok(dependencies()));
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Ok(dependencies()));
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.3"))
+            .times(1)
+            .returning(|_, _| Ok(dependencies()));
+
+        let registry = CachedRegistry::new(
+            "crates-io",
+            Box::new(mock),
+            CacheOptions {
+                max_entries: 2,
+                ..CacheOptions::default()
+            },
+        );
+
+        for version in &["0.8.1", "0.8.2", "0.8.2", "0.8.3", "0.8.2", "0.8.1"] {
+            registry
+                .get_crate_dependencies("rand", version)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_evicted_beyond_max_bytes() {
+        let mut mock = MockBar::new();
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.1"))
+            .times(2)
+            .returning(|_, _| Ok(dependencies()));
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Ok(dependencies()));
+
+        // room for the 10 dependencies of one version, not two
+        let registry = CachedRegistry::new(
+            "crates-io",
+            Box::new(mock),
+            CacheOptions {
+                max_bytes: 11 * DEPENDENCY_BYTES,
+                ..CacheOptions::default()
+            },
+        );
+
+        for version in &["0.8.1", "0.8.2", "0.8.2", "0.8.1"] {
+            registry
+                .get_crate_dependencies("rand", version)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_download_crate_kept_in_artifact_store() {
+        let mut mock = MockBar::new();
+        mock.expect_get_crate()
+            .with(eq("rand"))
+            .times(1)
+            .returning(|_| Ok(crate_with_checksum("0.8.2", &artifacts::sha256(b"crate"))));
+        mock.expect_download_verified()
+            .with(
+                eq("rand"),
+                eq("0.8.2"),
+                function(|checksum: &str| checksum == artifacts::sha256(b"crate")),
+            )
+            .times(1)
+            .returning(|_, _, _| Ok(b"crate".to_vec()));
+
+        let registry = CachedRegistry::new("crates-io", Box::new(mock), CacheOptions::default())
+            .artifacts(Arc::new(artifact_store()));
+
+        for _ in 0..3 {
+            let body = registry.download_crate("rand", "0.8.2").await.unwrap();
+            assert_eq!(body, b"crate");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_download_crate_takes_checksum_from_cached_crate() {
+        let mut mock = MockBar::new();
+        mock.expect_get_crate()
+            .with(eq("rand"))
+            .times(1)
+            .returning(|_| Ok(crate_with_checksum("0.8.2", &artifacts::sha256(b"crate"))));
+        mock.expect_download_verified()
+            .with(
+                eq("rand"),
+                eq("0.8.2"),
+                function(|checksum: &str| checksum == artifacts::sha256(b"crate")),
+            )
+            .times(2)
+            .returning(|_, _, _| Ok(b"crate".to_vec()));
+
+        let registry = CachedRegistry::new("crates-io", Box::new(mock), CacheOptions::default());
+
+        for _ in 0..2 {
+            let body = registry.download_crate("rand", "0.8.2").await.unwrap();
+            assert_eq!(body, b"crate");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_download_crate_not_matching_checksum_not_kept() {
+        let mut mock = MockBar::new();
+        mock.expect_get_crate()
+            .with(eq("rand"))
+            .times(1)
+            .returning(|_| Ok(crate_with_checksum("0.8.2", &artifacts::sha256(b"crate"))));
+        mock.expect_download_verified()
+            .with(
+                eq("rand"),
+                eq("0.8.2"),
+                function(|checksum: &str| checksum == artifacts::sha256(b"crate")),
+            )
+            .times(2)
+            .returning(|_, _, _| Ok(b"tampered".to_vec()));
+
+        let registry = CachedRegistry::new("crates-io", Box::new(mock), CacheOptions::default())
+            .artifacts(Arc::new(artifact_store()));
+
+        for _ in 0..2 {
+            assert!(matches!(
+                registry.download_crate("rand", "0.8.2").await,
+                Err(RustKataError::Integrity)
+            ));
+        }
+    }
+
+    fn crate_with_checksum(version: &str, checksum: &str) -> get_crate::Response {
+        let mut response = crate_();
+        for v in response.versions.iter_mut() {
+            if v.num == version {
+                v.checksum = Some(checksum.to_owned());
+            }
+        }
+        response
+    }
+
+    fn artifact_store() -> ArtifactStore {
+        ArtifactStore::open(std::env::temp_dir().join(uuid::Uuid::new_v4().to_string())).unwrap()
+    }
+
+    fn crate_() -> get_crate::Response {
+        serde_json::from_str(
+            &std::fs::read_to_string("./tests/fixtures/get_crates_rand.json").unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn dependencies() -> get_crate_dependencies::Response {
+        serde_json::from_str(
+            &std::fs::read_to_string("./tests/fixtures/get_crate_dependencies_rand_0.8.2.json")
+                .unwrap(),
+        )
+        .unwrap()
+    }
+}
@@ -0,0 +1,278 @@
+use crate::errors::RustKataResult;
+use crate::interfaces::crate_registry::{
+    get_categories, get_category, get_crate, get_crate_dependencies, get_crate_downloads,
+    get_crate_owners, get_crate_versions, get_keyword, get_keywords, get_reverse_dependencies,
+    get_summary, get_version_authors, search, CrateRegistry, Page,
+};
+use crate::observability::metrics;
+use async_trait::async_trait;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+type InFlight<T> = Shared<BoxFuture<'static, RustKataResult<T>>>;
+
+/// Single-flight deduplication: concurrent callers asking for the same key share one
+/// upstream call and its result, errors included. Nothing is kept once the call completes.
+/// Only crates and dependencies, which resolution asks for over and over, are coalesced.
+pub struct CoalescingRegistry {
+    crate_registry: Arc<dyn CrateRegistry>,
+    crates: Mutex<HashMap<String, InFlight<get_crate::Response>>>,
+    dependencies: Mutex<HashMap<(String, String), InFlight<get_crate_dependencies::Response>>>,
+}
+
+impl CoalescingRegistry {
+    pub fn new(crate_registry: Box<dyn CrateRegistry>) -> Self {
+        CoalescingRegistry {
+            crate_registry: Arc::from(crate_registry),
+            crates: Mutex::new(HashMap::new()),
+            dependencies: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl CrateRegistry for CoalescingRegistry {
+    async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response> {
+        let crate_registry = self.crate_registry.clone();
+        let crate_name_owned = crate_name.to_owned();
+
+        coalesce(
+            &self.crates,
+            crate_name.to_owned(),
+            "get_crate",
+            async move { crate_registry.get_crate(&crate_name_owned).await },
+        )
+        .await
+    }
+
+    async fn get_crate_dependencies(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_dependencies::Response> {
+        let crate_registry = self.crate_registry.clone();
+        let crate_name_owned = crate_name.to_owned();
+        let crate_version_owned = crate_version.to_owned();
+
+        coalesce(
+            &self.dependencies,
+            (crate_name.to_owned(), crate_version.to_owned()),
+            "get_crate_dependencies",
+            async move {
+                crate_registry
+                    .get_crate_dependencies(&crate_name_owned, &crate_version_owned)
+                    .await
+            },
+        )
+        .await
+    }
+
+    async fn get_crate_versions(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_versions::Response> {
+        self.crate_registry.get_crate_versions(crate_name).await
+    }
+
+    async fn get_crate_owners(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_owners::Response> {
+        self.crate_registry.get_crate_owners(crate_name).await
+    }
+
+    async fn get_crate_downloads(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_downloads::Response> {
+        self.crate_registry.get_crate_downloads(crate_name).await
+    }
+
+    async fn get_version_downloads(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_downloads::Response> {
+        self.crate_registry
+            .get_version_downloads(crate_name, crate_version)
+            .await
+    }
+
+    async fn get_version_authors(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_version_authors::Response> {
+        self.crate_registry
+            .get_version_authors(crate_name, crate_version)
+            .await
+    }
+
+    async fn get_version_readme(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<String> {
+        self.crate_registry
+            .get_version_readme(crate_name, crate_version)
+            .await
+    }
+
+    async fn get_reverse_dependencies(
+        &self,
+        crate_name: &str,
+        page: &Page,
+    ) -> RustKataResult<get_reverse_dependencies::Response> {
+        self.crate_registry
+            .get_reverse_dependencies(crate_name, page)
+            .await
+    }
+
+    async fn get_keywords(&self, page: &Page) -> RustKataResult<get_keywords::Response> {
+        self.crate_registry.get_keywords(page).await
+    }
+
+    async fn get_keyword(&self, keyword: &str) -> RustKataResult<get_keyword::Response> {
+        self.crate_registry.get_keyword(keyword).await
+    }
+
+    async fn get_categories(&self, page: &Page) -> RustKataResult<get_categories::Response> {
+        self.crate_registry.get_categories(page).await
+    }
+
+    async fn get_category(&self, category: &str) -> RustKataResult<get_category::Response> {
+        self.crate_registry.get_category(category).await
+    }
+
+    async fn get_summary(&self) -> RustKataResult<get_summary::Response> {
+        self.crate_registry.get_summary().await
+    }
+
+    async fn search(&self, query: &search::Query) -> RustKataResult<search::Response> {
+        self.crate_registry.search(query).await
+    }
+
+    async fn download_verified(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+        checksum: &str,
+    ) -> RustKataResult<Vec<u8>> {
+        self.crate_registry
+            .download_verified(crate_name, crate_version, checksum)
+            .await
+    }
+}
+
+async fn coalesce<K, T, F>(
+    in_flight: &Mutex<HashMap<K, InFlight<T>>>,
+    key: K,
+    endpoint: &str,
+    call: F,
+) -> RustKataResult<T>
+where
+    K: Clone + Eq + Hash,
+    T: Clone + Send + Sync + 'static,
+    F: Future<Output = RustKataResult<T>> + Send + 'static,
+{
+    let shared = {
+        let mut in_flight = in_flight.lock().unwrap();
+        match in_flight.get(&key) {
+            Some(shared) => {
+                metrics::registry_coalesced_count(endpoint);
+                shared.clone()
+            }
+            None => {
+                let shared = call.boxed().shared();
+                in_flight.insert(key.clone(), shared.clone());
+                shared
+            }
+        }
+    };
+
+    let result = shared.clone().await;
+
+    let mut in_flight = in_flight.lock().unwrap();
+    if let Some(current) = in_flight.get(&key) {
+        if current.ptr_eq(&shared) {
+            in_flight.remove(&key);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::RustKataError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[actix_rt::test]
+    async fn test_concurrent_calls_coalesced() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let registry = CoalescingRegistry::new(Box::new(SlowRegistry(calls.clone())));
+
+        let (a, b, c, d) = futures::join!(
+            registry.get_crate_dependencies("rand", "0.8.2"),
+            registry.get_crate_dependencies("rand", "0.8.2"),
+            registry.get_crate("rand"),
+            registry.get_crate("rand"),
+        );
+
+        assert!(a.is_ok());
+        assert!(b.is_ok());
+        assert!(c.is_err());
+        assert!(d.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(registry.crates.lock().unwrap().is_empty());
+        assert!(registry.dependencies.lock().unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_sequential_calls_not_coalesced() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let registry = CoalescingRegistry::new(Box::new(SlowRegistry(calls.clone())));
+
+        registry
+            .get_crate_dependencies("rand", "0.8.2")
+            .await
+            .unwrap();
+        registry
+            .get_crate_dependencies("rand", "0.8.2")
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    struct SlowRegistry(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl CrateRegistry for SlowRegistry {
+        async fn get_crate(&self, _: &str) -> RustKataResult<get_crate::Response> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            actix_rt::time::sleep(Duration::from_millis(20)).await;
+            Err(RustKataError::Internal)
+        }
+
+        async fn get_crate_dependencies(
+            &self,
+            _: &str,
+            _: &str,
+        ) -> RustKataResult<get_crate_dependencies::Response> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            actix_rt::time::sleep(Duration::from_millis(20)).await;
+            Ok(get_crate_dependencies::Response {
+                dependencies: vec![],
+                served_by: None,
+                upstream: Default::default(),
+                extra: Default::default(),
+            })
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use crate::errors::{RustKataError, RustKataResult};
+use crate::interfaces::crate_registry::{
+    get_categories, get_category, get_crate, get_crate_dependencies, get_crate_downloads,
+    get_crate_owners, get_crate_versions, get_keyword, get_keywords, get_reverse_dependencies,
+    get_summary, get_version_authors, search, CrateRegistry, Page,
+};
+use crate::observability::metrics;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use std::str::FromStr;
+
+/// Errors of a backend that let a call fall through to the next one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fallthrough {
+    /// Only crates and versions the backend does not know, for a mirror that may lag
+    /// behind but whose outages should not be papered over.
+    NotFound,
+    /// Only upstream failures, for a backend that is authoritative about what it knows.
+    Failure,
+    Any,
+}
+
+impl Fallthrough {
+    fn reason(&self, err: &RustKataError) -> Option<&'static str> {
+        let reason = match err {
+            RustKataError::NotFound => "not_found",
+            RustKataError::Unsupported => "unsupported",
+            _ => "failure",
+        };
+
+        match (self, reason) {
+            // a backend without the data at all is never authoritative about it
+            (_, "unsupported")
+            | (Fallthrough::Any, _)
+            | (Fallthrough::NotFound, "not_found")
+            | (Fallthrough::Failure, "failure") => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Fallthrough {
+    type Err = RustKataError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "not_found" => Ok(Fallthrough::NotFound),
+            "failure" => Ok(Fallthrough::Failure),
+            "any" => Ok(Fallthrough::Any),
+            _ => Err(RustKataError::InvalidInput {
+                description: format!("unknown fallthrough {}", s),
+            }),
+        }
+    }
+}
+
+/// Chain of backends asked in order, e.g. an internal mirror, then the sparse index, then
+/// the crates.io api. Responses name the backend that served them in `served_by`.
+#[derive(Default)]
+pub struct FallbackRegistry {
+    backends: Vec<Backend>,
+}
+
+struct Backend {
+    name: String,
+    crate_registry: Box<dyn CrateRegistry>,
+    fallthrough: Fallthrough,
+}
+
+impl FallbackRegistry {
+    pub fn new() -> Self {
+        FallbackRegistry::default()
+    }
+
+    /// Appends a backend, whose errors matching `fallthrough` go on to the next one. The
+    /// error of the last backend is always returned.
+    pub fn backend(
+        mut self,
+        name: &str,
+        crate_registry: Box<dyn CrateRegistry>,
+        fallthrough: Fallthrough,
+    ) -> Self {
+        self.backends.push(Backend {
+            name: name.to_owned(),
+            crate_registry,
+            fallthrough,
+        });
+        self
+    }
+
+    async fn call<'a, T, F>(&'a self, endpoint: &str, call: F) -> RustKataResult<(T, &'a str)>
+    where
+        F: Fn(&'a dyn CrateRegistry) -> BoxFuture<'a, RustKataResult<T>>,
+    {
+        for (index, backend) in self.backends.iter().enumerate() {
+            let err = match call(backend.crate_registry.as_ref()).await {
+                Ok(response) => return Ok((response, &backend.name)),
+                Err(err) => err,
+            };
+
+            let last = index + 1 == self.backends.len();
+            match backend.fallthrough.reason(&err) {
+                Some(reason) if !last => {
+                    log::debug!("{} {}: {}, falling through", backend.name, endpoint, err);
+                    metrics::registry_fallthrough_count(&backend.name, endpoint, reason);
+                }
+                _ => return Err(err),
+            }
+        }
+
+        // only without any backend at all
+        Err(RustKataError::Internal)
+    }
+}
+
+#[async_trait]
+impl CrateRegistry for FallbackRegistry {
+    async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response> {
+        let (mut response, served_by) = self
+            .call("get_crate", |crate_registry| {
+                crate_registry.get_crate(crate_name)
+            })
+            .await?;
+
+        response.served_by = Some(served_by.to_owned());
+        Ok(response)
+    }
+
+    async fn get_crate_dependencies(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_dependencies::Response> {
+        let (mut response, served_by) = self
+            .call("get_crate_dependencies", |crate_registry| {
+                crate_registry.get_crate_dependencies(crate_name, crate_version)
+            })
+            .await?;
+
+        response.served_by = Some(served_by.to_owned());
+        Ok(response)
+    }
+
+    async fn get_crate_versions(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_versions::Response> {
+        let (response, _) = self
+            .call("get_crate_versions", |crate_registry| {
+                crate_registry.get_crate_versions(crate_name)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_crate_owners(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_owners::Response> {
+        let (response, _) = self
+            .call("get_crate_owners", |crate_registry| {
+                crate_registry.get_crate_owners(crate_name)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_crate_downloads(
+        &self,
+        crate_name: &str,
+    ) -> RustKataResult<get_crate_downloads::Response> {
+        let (response, _) = self
+            .call("get_crate_downloads", |crate_registry| {
+                crate_registry.get_crate_downloads(crate_name)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_version_downloads(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_crate_downloads::Response> {
+        let (response, _) = self
+            .call("get_version_downloads", |crate_registry| {
+                crate_registry.get_version_downloads(crate_name, crate_version)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_version_authors(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<get_version_authors::Response> {
+        let (response, _) = self
+            .call("get_version_authors", |crate_registry| {
+                crate_registry.get_version_authors(crate_name, crate_version)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_version_readme(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+    ) -> RustKataResult<String> {
+        let (response, _) = self
+            .call("get_version_readme", |crate_registry| {
+                crate_registry.get_version_readme(crate_name, crate_version)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_reverse_dependencies(
+        &self,
+        crate_name: &str,
+        page: &Page,
+    ) -> RustKataResult<get_reverse_dependencies::Response> {
+        let (response, _) = self
+            .call("get_reverse_dependencies", |crate_registry| {
+                crate_registry.get_reverse_dependencies(crate_name, page)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_keywords(&self, page: &Page) -> RustKataResult<get_keywords::Response> {
+        let (response, _) = self
+            .call("get_keywords", |crate_registry| {
+                crate_registry.get_keywords(page)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_keyword(&self, keyword: &str) -> RustKataResult<get_keyword::Response> {
+        let (response, _) = self
+            .call("get_keyword", |crate_registry| {
+                crate_registry.get_keyword(keyword)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_categories(&self, page: &Page) -> RustKataResult<get_categories::Response> {
+        let (response, _) = self
+            .call("get_categories", |crate_registry| {
+                crate_registry.get_categories(page)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_category(&self, category: &str) -> RustKataResult<get_category::Response> {
+        let (response, _) = self
+            .call("get_category", |crate_registry| {
+                crate_registry.get_category(category)
+            })
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn get_summary(&self) -> RustKataResult<get_summary::Response> {
+        let (response, _) = self
+            .call("get_summary", |crate_registry| crate_registry.get_summary())
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn search(&self, query: &search::Query) -> RustKataResult<search::Response> {
+        let (response, _) = self
+            .call("search", |crate_registry| crate_registry.search(query))
+            .await?;
+
+        Ok(response)
+    }
+
+    async fn download_verified(
+        &self,
+        crate_name: &str,
+        crate_version: &str,
+        checksum: &str,
+    ) -> RustKataResult<Vec<u8>> {
+        let (response, _) = self
+            .call("download_crate", |crate_registry| {
+                crate_registry.download_verified(crate_name, crate_version, checksum)
+            })
+            .await?;
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::mock::MockBar;
+    use mockall::predicate::*;
+
+    #[actix_rt::test]
+    async fn test_falls_through_not_found() {
+        let mut mirror = MockBar::new();
+        mirror
+            .expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Err(RustKataError::NotFound));
+
+        let mut api = MockBar::new();
+        api.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Ok(dependencies()));
+
+        let registry = FallbackRegistry::new()
+            .backend("mirror", Box::new(mirror), Fallthrough::NotFound)
+            .backend("api", Box::new(api), Fallthrough::Any);
+
+        let response = registry
+            .get_crate_dependencies("rand", "0.8.2")
+            .await
+            .unwrap();
+
+        assert_eq!(response.served_by, Some("api".to_owned()));
+    }
+
+    #[actix_rt::test]
+    async fn test_serves_from_first_backend() {
+        let mut mirror = MockBar::new();
+        mirror
+            .expect_get_crate_dependencies()
+            .times(1)
+            .returning(|_, _| Ok(dependencies()));
+
+        let mut api = MockBar::new();
+        api.expect_get_crate_dependencies().times(0);
+
+        let registry = FallbackRegistry::new()
+            .backend("mirror", Box::new(mirror), Fallthrough::Any)
+            .backend("api", Box::new(api), Fallthrough::Any);
+
+        let response = registry
+            .get_crate_dependencies("rand", "0.8.2")
+            .await
+            .unwrap();
+
+        assert_eq!(response.served_by, Some("mirror".to_owned()));
+    }
+
+    #[actix_rt::test]
+    async fn test_policy_stops_fallthrough() {
+        let mut mirror = MockBar::new();
+        mirror
+            .expect_get_crate_dependencies()
+            .times(1)
+            .returning(|_, _| Err(RustKataError::NotFound));
+
+        let mut api = MockBar::new();
+        api.expect_get_crate_dependencies().times(0);
+
+        let registry = FallbackRegistry::new()
+            .backend("internal", Box::new(mirror), Fallthrough::Failure)
+            .backend("api", Box::new(api), Fallthrough::Any);
+
+        assert!(matches!(
+            registry.get_crate_dependencies("rand", "0.8.2").await,
+            Err(RustKataError::NotFound)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_returns_last_error() {
+        let mut mirror = MockBar::new();
+        mirror
+            .expect_get_crate_dependencies()
+            .times(1)
+            .returning(|_, _| Err(RustKataError::Transport));
+
+        let mut api = MockBar::new();
+        api.expect_get_crate_dependencies()
+            .times(1)
+            .returning(|_, _| Err(RustKataError::NotFound));
+
+        let registry = FallbackRegistry::new()
+            .backend("mirror", Box::new(mirror), Fallthrough::Any)
+            .backend("api", Box::new(api), Fallthrough::Any);
+
+        assert!(matches!(
+            registry.get_crate_dependencies("rand", "0.8.2").await,
+            Err(RustKataError::NotFound)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_falls_through_unsupported() {
+        let mut api = MockBar::new();
+        api.expect_get_crate_owners()
+            .with(eq("rand"))
+            .times(1)
+            .returning(|_| Ok(get_crate_owners::Response::default()));
+
+        let registry = FallbackRegistry::new()
+            .backend("index", Box::new(Unsupported), Fallthrough::NotFound)
+            .backend("api", Box::new(api), Fallthrough::Any);
+
+        assert!(registry.get_crate_owners("rand").await.is_ok());
+    }
+
+    struct Unsupported;
+
+    #[async_trait]
+    impl CrateRegistry for Unsupported {
+        async fn get_crate(&self, _: &str) -> RustKataResult<get_crate::Response> {
+            Err(RustKataError::Unsupported)
+        }
+
+        async fn get_crate_dependencies(
+            &self,
+            _: &str,
+            _: &str,
+        ) -> RustKataResult<get_crate_dependencies::Response> {
+            Err(RustKataError::Unsupported)
+        }
+    }
+
+    fn dependencies() -> get_crate_dependencies::Response {
+        get_crate_dependencies::Response {
+            dependencies: vec![],
+            served_by: None,
+            upstream: Default::default(),
+            extra: Default::default(),
+        }
+    }
+}
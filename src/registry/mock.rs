@@ -0,0 +1,18 @@
+use crate::errors::RustKataResult;
+use crate::interfaces::crate_registry::{
+    get_crate, get_crate_dependencies, get_crate_owners, CrateRegistry,
+};
+use async_trait::async_trait;
+use mockall::mock;
+
+mock! {
+    pub Bar {}
+
+    #[async_trait]
+    impl CrateRegistry for Bar {
+        async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response>;
+        async fn get_crate_dependencies(&self, crate_name: &str, crate_version: &str) -> RustKataResult<get_crate_dependencies::Response>;
+        async fn get_crate_owners(&self, crate_name: &str) -> RustKataResult<get_crate_owners::Response>;
+        async fn download_verified(&self, crate_name: &str, crate_version: &str, checksum: &str) -> RustKataResult<Vec<u8>>;
+    }
+}
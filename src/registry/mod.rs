@@ -0,0 +1,9 @@
+pub mod artifacts;
+pub mod cached;
+pub mod coalescing;
+pub mod fallback;
+pub mod registries;
+pub mod store;
+
+#[cfg(test)]
+pub(crate) mod mock;
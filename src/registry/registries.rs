@@ -0,0 +1,147 @@
+use crate::interfaces::crate_registry::CrateRegistry;
+use std::collections::{HashMap, HashSet};
+
+/// Index url of crates.io, as dependencies published to other registries refer to it.
+pub const CRATES_IO_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+
+/// Named registries the service can query. Requests that do not name one go to the default.
+///
+/// Dependencies published to one registry can live in another, in which case they refer to
+/// it by its index url. Each registry can be known by any number of index urls.
+pub struct Registries {
+    default: String,
+    registries: HashMap<String, Box<dyn CrateRegistry>>,
+    indexes: HashMap<String, String>,
+    private: HashSet<String>,
+}
+
+impl Registries {
+    pub fn new(default: &str, crate_registry: Box<dyn CrateRegistry>) -> Self {
+        Registries {
+            default: default.to_owned(),
+            registries: HashMap::new(),
+            indexes: HashMap::new(),
+            private: HashSet::new(),
+        }
+        .registry(default, crate_registry)
+    }
+
+    pub fn registry(mut self, name: &str, crate_registry: Box<dyn CrateRegistry>) -> Self {
+        self.registries.insert(name.to_owned(), crate_registry);
+        self
+    }
+
+    /// Dependencies referring to `index_url` are looked up in the registry called `name`.
+    pub fn index(mut self, index_url: &str, name: &str) -> Self {
+        self.indexes
+            .insert(normalize(index_url).to_owned(), name.to_owned());
+        self
+    }
+
+    /// The registry called `name` is only reachable with credentials, so what it serves is
+    /// not to be shared between clients.
+    pub fn private(mut self, name: &str) -> Self {
+        self.private.insert(name.to_owned());
+        self
+    }
+
+    pub fn default_name(&self) -> &str {
+        &self.default
+    }
+
+    pub fn default_registry(&self) -> &dyn CrateRegistry {
+        self.registries[&self.default].as_ref()
+    }
+
+    /// The registry called `name`, or the default one when no name is given.
+    pub fn get(&self, name: Option<&str>) -> Option<&dyn CrateRegistry> {
+        self.registries
+            .get(name.unwrap_or(&self.default))
+            .map(AsRef::as_ref)
+    }
+
+    /// Whether the registry called `name`, or the default one, is private.
+    pub fn is_private(&self, name: Option<&str>) -> bool {
+        self.private.contains(name.unwrap_or(&self.default))
+    }
+
+    /// `name`, if it is given but no registry is called that.
+    pub fn unknown<'b>(&self, name: Option<&'b str>) -> Option<&'b str> {
+        name.filter(|name| !self.registries.contains_key(*name))
+    }
+
+    /// Name of the registry known by `index_url`.
+    pub fn by_index(&self, index_url: &str) -> Option<&str> {
+        self.indexes.get(normalize(index_url)).map(String::as_str)
+    }
+}
+
+/// Cargo writes the same index as `sparse+https://host/path/`, `https://host/path.git` and so
+/// on, depending on the protocol and on who published the crate.
+fn normalize(index_url: &str) -> &str {
+    let index_url = index_url
+        .strip_prefix("sparse+")
+        .or_else(|| index_url.strip_prefix("registry+"))
+        .unwrap_or(index_url);
+    let index_url = index_url.trim_end_matches('/');
+    index_url.strip_suffix(".git").unwrap_or(index_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::local::LocalIndex;
+
+    #[test]
+    fn test_get() {
+        let registries = Registries::new("crates-io", Box::new(LocalIndex::new("a")))
+            .registry("internal", Box::new(LocalIndex::new("b")));
+
+        assert_eq!(registries.default_name(), "crates-io");
+        assert!(registries.get(None).is_some());
+        assert!(registries.get(Some("internal")).is_some());
+        assert!(registries.get(Some("unknown")).is_none());
+
+        assert_eq!(registries.unknown(None), None);
+        assert_eq!(registries.unknown(Some("internal")), None);
+        assert_eq!(registries.unknown(Some("unknown")), Some("unknown"));
+    }
+
+    #[test]
+    fn test_is_private() {
+        let registries = Registries::new("crates-io", Box::new(LocalIndex::new("a")))
+            .registry("internal", Box::new(LocalIndex::new("b")))
+            .private("internal");
+
+        assert!(!registries.is_private(None));
+        assert!(!registries.is_private(Some("crates-io")));
+        assert!(registries.is_private(Some("internal")));
+    }
+
+    #[test]
+    fn test_by_index() {
+        let registries = Registries::new("crates-io", Box::new(LocalIndex::new("a")))
+            .index(CRATES_IO_INDEX, "crates-io")
+            .index("sparse+https://index.crates.io/", "crates-io")
+            .registry("internal", Box::new(LocalIndex::new("b")))
+            .index("https://git.example.com/registry/index.git", "internal");
+
+        assert_eq!(
+            registries.by_index("https://github.com/rust-lang/crates.io-index"),
+            Some("crates-io")
+        );
+        assert_eq!(
+            registries.by_index("registry+https://github.com/rust-lang/crates.io-index.git"),
+            Some("crates-io")
+        );
+        assert_eq!(
+            registries.by_index("sparse+https://index.crates.io/"),
+            Some("crates-io")
+        );
+        assert_eq!(
+            registries.by_index("https://git.example.com/registry/index"),
+            Some("internal")
+        );
+        assert_eq!(registries.by_index("https://example.org/index"), None);
+    }
+}
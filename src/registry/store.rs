@@ -0,0 +1,256 @@
+use crate::errors::{RustKataError, RustKataResult};
+use actix_web::web;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredResponse {
+    pub body: Vec<u8>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Unix timestamp, in seconds, of when the response was stored.
+    pub stored_at: i64,
+}
+
+/// Raw upstream responses keyed by URL, kept in SQLite so they survive restarts. Queries
+/// run on the blocking thread pool, never on the workers.
+///
+/// Once bodies take more than `max_bytes`, the responses stored or revalidated the longest
+/// ago are evicted. Eviction is first in, first out: reading a response does not keep it
+/// longer, so reads never write.
+pub struct DiskStore {
+    state: Arc<Mutex<State>>,
+    max_bytes: u64,
+}
+
+/// The connection and the total size of the bodies it holds, kept as they change so that
+/// a put only looks at the rows it evicts.
+struct State {
+    connection: Connection,
+    bytes: u64,
+}
+
+impl DiskStore {
+    /// Half of the volume the helm chart provisions, which artifacts share.
+    pub const DEFAULT_MAX_BYTES: u64 = 512 * 1024 * 1024;
+
+    pub fn open<P: AsRef<Path>>(path: P) -> RustKataResult<Self> {
+        DiskStore::initialize(Connection::open(path).map_err(|_| RustKataError::Internal)?)
+    }
+
+    pub fn open_in_memory() -> RustKataResult<Self> {
+        DiskStore::initialize(Connection::open_in_memory().map_err(|_| RustKataError::Internal)?)
+    }
+
+    fn initialize(connection: Connection) -> RustKataResult<Self> {
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS responses (
+                    url TEXT PRIMARY KEY,
+                    body BLOB NOT NULL,
+                    etag TEXT,
+                    last_modified TEXT,
+                    stored_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS responses_stored_at ON responses (stored_at);",
+            )
+            .map_err(|_| RustKataError::Internal)?;
+
+        let bytes: i64 = connection
+            .query_row(
+                "SELECT COALESCE(SUM(LENGTH(body)), 0) FROM responses",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|_| RustKataError::Internal)?;
+
+        Ok(DiskStore {
+            state: Arc::new(Mutex::new(State {
+                connection,
+                bytes: bytes as u64,
+            })),
+            max_bytes: DiskStore::DEFAULT_MAX_BYTES,
+        })
+    }
+
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub async fn get(&self, url: &str) -> Option<StoredResponse> {
+        let state = self.state.clone();
+        let key = url.to_owned();
+
+        web::block(move || {
+            state
+                .lock()
+                .unwrap()
+                .connection
+                .query_row(
+                    "SELECT body, etag, last_modified, stored_at FROM responses WHERE url = ?1",
+                    params![key],
+                    |row| {
+                        Ok(StoredResponse {
+                            body: row.get(0)?,
+                            etag: row.get(1)?,
+                            last_modified: row.get(2)?,
+                            stored_at: row.get(3)?,
+                        })
+                    },
+                )
+                .optional()
+        })
+        .await
+        .unwrap_or_else(|err| {
+            log::warn!("failed to read {} from disk store: {}", url, err);
+            None
+        })
+    }
+
+    pub async fn put(
+        &self,
+        url: &str,
+        body: &[u8],
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) {
+        let state = self.state.clone();
+        let max_bytes = self.max_bytes;
+        let (key, body) = (url.to_owned(), body.to_vec());
+        let (etag, last_modified) = (etag.map(str::to_owned), last_modified.map(str::to_owned));
+
+        if let Err(err) = web::block(move || {
+            let mut state = state.lock().unwrap();
+            let state = &mut *state;
+
+            let replaced: Option<i64> = state
+                .connection
+                .query_row(
+                    "SELECT LENGTH(body) FROM responses WHERE url = ?1",
+                    params![key],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let stored = body.len() as u64;
+
+            state.connection.execute(
+                "INSERT OR REPLACE INTO responses (url, body, etag, last_modified, stored_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![key, body, etag, last_modified, Utc::now().timestamp()],
+            )?;
+            state.bytes = state
+                .bytes
+                .saturating_sub(replaced.unwrap_or_default() as u64)
+                + stored;
+
+            if state.bytes <= max_bytes {
+                return Ok(());
+            }
+
+            // a replaced row gets a new rowid, which orders it after others stored the same
+            // second
+            let mut oldest = state
+                .connection
+                .prepare("SELECT url, LENGTH(body) FROM responses ORDER BY stored_at, rowid")?;
+            let mut rows = oldest.query([])?;
+            while state.bytes > max_bytes {
+                let row = match rows.next()? {
+                    Some(row) => row,
+                    None => break,
+                };
+                let (url, length): (String, i64) = (row.get(0)?, row.get(1)?);
+
+                state
+                    .connection
+                    .execute("DELETE FROM responses WHERE url = ?1", params![url])?;
+                state.bytes = state.bytes.saturating_sub(length as u64);
+            }
+
+            Ok::<_, rusqlite::Error>(())
+        })
+        .await
+        {
+            log::warn!("failed to write {} to disk store: {}", url, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_put_get() {
+        let store = DiskStore::open_in_memory().unwrap();
+
+        assert_eq!(
+            store.get("https://crates.io/api/v1/crates/rand").await,
+            None
+        );
+
+        store
+            .put(
+                "https://crates.io/api/v1/crates/rand",
+                b"{}",
+                Some("W/\"1\""),
+                None,
+            )
+            .await;
+        store
+            .put(
+                "https://crates.io/api/v1/crates/rand",
+                b"{\"crate\":{}}",
+                Some("W/\"2\""),
+                Some("Wed, 13 Jan 2021 09:55:20 GMT"),
+            )
+            .await;
+
+        let stored = store
+            .get("https://crates.io/api/v1/crates/rand")
+            .await
+            .unwrap();
+
+        assert_eq!(stored.body, b"{\"crate\":{}}".to_vec());
+        assert_eq!(stored.etag, Some("W/\"2\"".to_owned()));
+        assert_eq!(
+            stored.last_modified,
+            Some("Wed, 13 Jan 2021 09:55:20 GMT".to_owned())
+        );
+        assert!(stored.stored_at > 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_evicts_beyond_max_bytes() {
+        let store = DiskStore::open_in_memory().unwrap().max_bytes(10);
+
+        store.put("https://crates.io/a", b"aaaa", None, None).await;
+        store.put("https://crates.io/b", b"bbbb", None, None).await;
+        // revalidated, so it is kept over b
+        store.put("https://crates.io/a", b"aaaa", None, None).await;
+        store.put("https://crates.io/c", b"cccc", None, None).await;
+
+        assert!(store.get("https://crates.io/a").await.is_some());
+        assert_eq!(store.get("https://crates.io/b").await, None);
+        assert!(store.get("https://crates.io/c").await.is_some());
+    }
+
+    #[actix_rt::test]
+    async fn test_counts_bodies_stored_before_opening() {
+        let path = std::env::temp_dir().join(format!("{}.sqlite", uuid::Uuid::new_v4()));
+
+        let store = DiskStore::open(&path).unwrap();
+        store.put("https://crates.io/a", b"aaaa", None, None).await;
+        store.put("https://crates.io/b", b"bbbb", None, None).await;
+        drop(store);
+
+        let store = DiskStore::open(&path).unwrap().max_bytes(10);
+        store.put("https://crates.io/c", b"cccc", None, None).await;
+
+        assert_eq!(store.get("https://crates.io/a").await, None);
+        assert!(store.get("https://crates.io/b").await.is_some());
+        assert!(store.get("https://crates.io/c").await.is_some());
+    }
+}
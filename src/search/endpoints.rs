@@ -0,0 +1,123 @@
+use crate::errors::{RustKataError, RustKataResult};
+use crate::interfaces::crate_registry::{search, CrateRegistry, Page};
+use crate::interfaces::http;
+use crate::registry::registries::Registries;
+use crate::search::models::{Hit, QueryParams, SearchResult};
+use crate::search::ranking;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use futures::future::join_all;
+
+/// Number of the registry's most relevant crates that are ranked.
+const CANDIDATES: usize = 50;
+/// Number of hits responded with when the query does not set a limit.
+const DEFAULT_LIMIT: usize = 10;
+
+pub fn config(cfg: &mut web::ServiceConfig, host_base_path: &str) {
+    let path = format!("{}/search", host_base_path);
+
+    cfg.service(
+        web::scope(&path)
+            .app_data(http::query_config())
+            .route("", web::get().to(query)),
+    );
+}
+
+async fn query(
+    web::Query(query_parameters): web::Query<QueryParams>,
+    registries: web::Data<Registries>,
+) -> RustKataResult<HttpResponse> {
+    let crate_registry = match registries.get(query_parameters.registry.as_deref()) {
+        Some(crate_registry) => crate_registry,
+        None => {
+            return Ok(http::unknown_registry(
+                query_parameters.registry.as_deref().unwrap_or_default(),
+            ))
+        }
+    };
+
+    let limit = query_parameters.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > CANDIDATES {
+        return Err(RustKataError::InvalidInput {
+            description: format!("invalid limit: must be between 1 and {}", CANDIDATES),
+        });
+    }
+
+    let response = crate_registry
+        .search(&search::Query {
+            q: query_parameters.q,
+            sort: Some(search::Sort::Relevance),
+            category: query_parameters.category,
+            keyword: query_parameters.keyword,
+            page: Page {
+                page: 1,
+                per_page: CANDIDATES,
+            },
+        })
+        .await?;
+
+    let mut ranked = ranking::rank(response.crates, Utc::now());
+    ranked.truncate(limit);
+
+    let dependencies =
+        match query_parameters.dependencies.unwrap_or(false) {
+            true => {
+                join_all(ranked.iter().map(|(crate_, _)| {
+                    dependencies(crate_registry, &crate_.name, &crate_.max_version)
+                }))
+                .await
+            }
+            false => vec![None; ranked.len()],
+        };
+
+    let data = ranked
+        .into_iter()
+        .zip(dependencies)
+        .map(|((crate_, score), dependencies)| Hit {
+            name: crate_.name,
+            max_version: crate_.max_version,
+            description: crate_.description,
+            repository: crate_.repository,
+            downloads: crate_.downloads,
+            recent_downloads: crate_.recent_downloads,
+            updated_at: crate_.updated_at,
+            score,
+            dependencies,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(SearchResult {
+        data,
+        total: response.meta.total,
+    }))
+}
+
+/// Number of direct dependencies of the version, dev-dependencies excluded. `None` when the
+/// registry cannot list them, which leaves the hit itself intact.
+async fn dependencies(
+    crate_registry: &dyn CrateRegistry,
+    crate_name: &str,
+    crate_version: &str,
+) -> Option<usize> {
+    match crate_registry
+        .get_crate_dependencies(crate_name, crate_version)
+        .await
+    {
+        Ok(response) => Some(
+            response
+                .dependencies
+                .iter()
+                .filter(|dependency| dependency.kind != "dev")
+                .count(),
+        ),
+        Err(err) => {
+            log::warn!(
+                "failed to count dependencies of {} {}: {}",
+                crate_name,
+                crate_version,
+                err
+            );
+            None
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::interfaces::validation;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+pub struct QueryParams {
+    #[serde(skip_serializing_if = "Option::is_none", rename = "q")]
+    pub q: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "category",
+        deserialize_with = "validation::deserialize_optional_slug"
+    )]
+    pub category: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "keyword",
+        deserialize_with = "validation::deserialize_optional_slug"
+    )]
+    pub keyword: Option<String>,
+    /// Number of hits to respond with, out of the candidates that are ranked.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "limit")]
+    pub limit: Option<usize>,
+    /// Whether to count the direct dependencies of each hit's `max_version`.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "dependencies")]
+    pub dependencies: Option<bool>,
+    /// Name of the registry to search. Defaults to the default registry.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "registry")]
+    pub registry: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SearchResult {
+    #[serde(rename = "data")]
+    pub data: Vec<Hit>,
+    /// Number of crates matching the query upstream, of which `data` ranks the top candidates.
+    #[serde(rename = "total")]
+    pub total: i64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Hit {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "max_version")]
+    pub max_version: String,
+    #[serde(rename = "description")]
+    pub description: Option<String>,
+    #[serde(rename = "repository")]
+    pub repository: Option<String>,
+    #[serde(rename = "downloads")]
+    pub downloads: i64,
+    #[serde(rename = "recent_downloads")]
+    pub recent_downloads: i64,
+    #[serde(rename = "updated_at")]
+    pub updated_at: String,
+    /// Between 0 and 1, higher for crates that are popular and actively maintained.
+    #[serde(rename = "score")]
+    pub score: f64,
+    /// Direct dependencies of `max_version`, dev-dependencies excluded. Only present when
+    /// asked for and the registry could list them.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "dependencies"
+    )]
+    pub dependencies: Option<usize>,
+}
@@ -0,0 +1,132 @@
+use crate::interfaces::crate_registry::get_crate::CrateResponse;
+use chrono::{DateTime, Utc};
+
+/// Weight of all-time downloads in a score.
+const DOWNLOADS_WEIGHT: f64 = 0.4;
+/// Weight of the downloads of the last 90 days in a score.
+const RECENT_DOWNLOADS_WEIGHT: f64 = 0.4;
+/// Weight of how recently a new version was published in a score.
+const FRESHNESS_WEIGHT: f64 = 0.2;
+/// Age at which a crate's freshness has halved.
+const FRESHNESS_HALF_LIFE_DAYS: f64 = 180.0;
+
+/// Orders `crates` by score, highest first. Ties keep the order they came in, so the
+/// registry's relevance decides between equally popular crates.
+pub fn rank(crates: Vec<CrateResponse>, now: DateTime<Utc>) -> Vec<(CrateResponse, f64)> {
+    let max_downloads = crates.iter().map(|c| c.downloads).max().unwrap_or_default();
+    let max_recent_downloads = crates
+        .iter()
+        .map(|c| c.recent_downloads)
+        .max()
+        .unwrap_or_default();
+
+    let mut ranked: Vec<_> = crates
+        .into_iter()
+        .map(|crate_| {
+            let score = DOWNLOADS_WEIGHT * popularity(crate_.downloads, max_downloads)
+                + RECENT_DOWNLOADS_WEIGHT
+                    * popularity(crate_.recent_downloads, max_recent_downloads)
+                + FRESHNESS_WEIGHT * freshness(&crate_.updated_at, now);
+            (crate_, score)
+        })
+        .collect();
+
+    ranked.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    ranked
+}
+
+/// Downloads relative to the most downloaded candidate, on a log scale so that one
+/// dominant crate does not flatten everyone else to zero.
+fn popularity(downloads: i64, max_downloads: i64) -> f64 {
+    if downloads <= 0 || max_downloads <= 0 {
+        return 0.0;
+    }
+
+    (downloads as f64).ln_1p() / (max_downloads as f64).ln_1p()
+}
+
+/// 1 for a crate updated `now`, halving every `FRESHNESS_HALF_LIFE_DAYS`. Unparsable
+/// timestamps count as stale.
+fn freshness(updated_at: &str, now: DateTime<Utc>) -> f64 {
+    match DateTime::parse_from_rfc3339(updated_at) {
+        Ok(updated_at) => {
+            let days = (now - updated_at.with_timezone(&Utc)).num_seconds().max(0) as f64
+                / (24 * 60 * 60) as f64;
+            0.5f64.powf(days / FRESHNESS_HALF_LIFE_DAYS)
+        }
+        Err(_) => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crate_(
+        name: &str,
+        downloads: i64,
+        recent_downloads: i64,
+        updated_at: &str,
+    ) -> CrateResponse {
+        CrateResponse {
+            name: name.to_owned(),
+            downloads,
+            recent_downloads,
+            updated_at: updated_at.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2021-02-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn names(ranked: &[(CrateResponse, f64)]) -> Vec<&str> {
+        ranked.iter().map(|(c, _)| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_popular_crates_rank_first() {
+        let ranked = rank(
+            vec![
+                crate_("obscure", 100, 10, "2021-01-31T00:00:00Z"),
+                crate_("popular", 10_000_000, 1_000_000, "2021-01-31T00:00:00Z"),
+            ],
+            now(),
+        );
+
+        assert_eq!(names(&ranked), vec!["popular", "obscure"]);
+        assert!(ranked[0].1 <= 1.0);
+        assert!(ranked[1].1 > 0.0);
+    }
+
+    #[test]
+    fn test_recent_activity_beats_past_popularity() {
+        let ranked = rank(
+            vec![
+                crate_("abandoned", 5_000_000, 1_000, "2018-01-01T00:00:00Z"),
+                crate_("successor", 3_000_000, 900_000, "2021-01-15T00:00:00Z"),
+            ],
+            now(),
+        );
+
+        assert_eq!(names(&ranked), vec!["successor", "abandoned"]);
+    }
+
+    #[test]
+    fn test_ties_keep_registry_order() {
+        let ranked = rank(
+            vec![
+                crate_("a", 0, 0, "invalid"),
+                crate_("b", 0, 0, "invalid"),
+                crate_("c", 0, 0, "invalid"),
+            ],
+            now(),
+        );
+
+        assert_eq!(names(&ranked), vec!["a", "b", "c"]);
+        assert!(ranked.iter().all(|(_, score)| *score == 0.0));
+    }
+}
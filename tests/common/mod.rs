@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use mockall::mock;
+
+use rust_kata_002::errors::RustKataResult;
+use rust_kata_002::interfaces::crate_registry::{
+    get_categories, get_crate, get_crate_dependencies, get_crate_owners, get_keyword, get_summary,
+    search, CrateRegistry, Page,
+};
+
+mock! {
+    pub Bar {}
+
+    #[async_trait]
+    impl CrateRegistry for Bar {
+        async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response>;
+        async fn get_crate_dependencies(&self, crate_name: &str, crate_version: &str) -> RustKataResult<get_crate_dependencies::Response>;
+        async fn get_crate_owners(&self, crate_name: &str) -> RustKataResult<get_crate_owners::Response>;
+        async fn get_version_readme(&self, crate_name: &str, crate_version: &str) -> RustKataResult<String>;
+        async fn get_keyword(&self, keyword: &str) -> RustKataResult<get_keyword::Response>;
+        async fn get_categories(&self, page: &Page) -> RustKataResult<get_categories::Response>;
+        async fn get_summary(&self) -> RustKataResult<get_summary::Response>;
+        async fn search(&self, query: &search::Query) -> RustKataResult<search::Response>;
+        async fn download_crate(&self, crate_name: &str, crate_version: &str) -> RustKataResult<Vec<u8>>;
+    }
+}
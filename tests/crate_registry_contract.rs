@@ -0,0 +1,192 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+    use serde_json::Value;
+
+    use rust_kata_002::errors::RustKataResult;
+    use rust_kata_002::interfaces::crate_registry::{get_crate, get_crate_dependencies};
+    use rust_kata_002::proxy;
+    use rust_kata_002::registry::registries::Registries;
+
+    #[test]
+    fn test_get_crate_contract() {
+        let fixtures = fixtures("get_crates_");
+        assert!(fixtures.len() >= 9);
+
+        for (path, original) in fixtures {
+            let response: get_crate::Response = serde_json::from_value(original.clone())
+                .unwrap_or_else(|err| panic!("{}: {}", path, err));
+
+            assert_passed_through(&path, &original, &serde_json::to_value(&response).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_get_crate_dependencies_contract() {
+        let fixtures = fixtures("get_crate_dependencies_");
+        assert!(fixtures.len() >= 2);
+
+        for (path, original) in fixtures {
+            let response: get_crate_dependencies::Response =
+                serde_json::from_value(original.clone())
+                    .unwrap_or_else(|err| panic!("{}: {}", path, err));
+
+            assert_passed_through(&path, &original, &serde_json::to_value(&response).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_get_crate_without_repository() {
+        let response: get_crate::Response =
+            serde_json::from_value(fixture("contract/get_crates_bare.json")).unwrap();
+
+        assert_eq!(response.crate_.description, None);
+        assert_eq!(response.crate_.repository, None);
+        assert_eq!(response.crate_.documentation, None);
+        assert!(response.crate_.badges.is_empty());
+        assert_eq!(response.versions[0].license, None);
+        assert!(response.versions[0].audit_actions.is_empty());
+    }
+
+    #[test]
+    fn test_get_crate_without_lists_or_avatar() {
+        let response: get_crate::Response =
+            serde_json::from_value(fixture("contract/get_crates_unlisted.json")).unwrap();
+
+        assert!(response.crate_.keywords.is_empty());
+        assert_eq!(response.crate_.links.owners, None);
+        assert_eq!(response.crate_.links.owner_team, None);
+        assert!(response.keywords.is_empty());
+        assert!(response.categories.is_empty());
+
+        let published_by = response.versions[0].published_by.as_ref().unwrap();
+        assert_eq!(published_by.login, "avatarless");
+        assert_eq!(published_by.avatar, None);
+
+        let mut nulls = fixture("contract/get_crates_unlisted.json");
+        for key in &["versions", "keywords", "categories"] {
+            nulls[key] = Value::Null;
+            nulls["crate"][key] = Value::Null;
+        }
+
+        let response: get_crate::Response = serde_json::from_value(nulls).unwrap();
+
+        assert!(response.versions.is_empty());
+        assert!(response.crate_.versions.is_empty());
+        assert!(response.crate_.categories.is_empty());
+    }
+
+    #[test]
+    fn test_error_body_is_not_a_crate() {
+        let error = serde_json::json!({"errors": [{"detail": "Not Found"}]});
+
+        assert!(serde_json::from_value::<get_crate::Response>(error.clone()).is_err());
+        assert!(serde_json::from_value::<get_crate_dependencies::Response>(error).is_err());
+
+        let mut nameless = fixture("contract/get_crates_bare.json");
+        nameless["crate"].as_object_mut().unwrap().remove("name");
+
+        assert!(serde_json::from_value::<get_crate::Response>(nameless).is_err());
+    }
+
+    #[test]
+    fn test_get_crate_badges() {
+        let response: get_crate::Response =
+            serde_json::from_value(fixture("contract/get_crates_badges.json")).unwrap();
+
+        let badges = &response.crate_.badges;
+
+        assert_eq!(badges[0].attributes.branch.as_deref(), Some("master"));
+        assert_eq!(badges[1].attributes.service.as_deref(), Some("github"));
+        assert_eq!(badges[2].attributes.branch, None);
+        assert_eq!(badges[3].attributes.repository, None);
+        assert_eq!(
+            badges[3].attributes.extra.get("status"),
+            Some(&Value::from("actively-developed"))
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_passes_unknown_fields_through() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate()
+            .with(eq("extended"))
+            .times(1)
+            .returning(|_| {
+                RustKataResult::Ok(
+                    serde_json::from_value(fixture("contract/get_crates_extended.json")).unwrap(),
+                )
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate?name=extended")
+            .to_request();
+
+        let result: Value = test::read_response_json(&mut app, request).await;
+
+        assert_eq!(result["meta"]["total"], 1);
+        assert_eq!(result["crate"]["default_version"], "0.0.1-alpha");
+        assert_eq!(result["versions"][0]["edition"], "2021");
+        assert_eq!(result["versions"][0]["linecounts"]["total_code_lines"], 120);
+    }
+
+    /// Every field of `original` must come back unchanged in `serialized`, which may only
+    /// add the defaults of fields that were missing.
+    fn assert_passed_through(path: &str, original: &Value, serialized: &Value) {
+        match (original, serialized) {
+            (Value::Object(original), Value::Object(serialized)) => {
+                for (key, value) in original {
+                    let path = format!("{}.{}", path, key);
+                    match serialized.get(key) {
+                        Some(serialized) => assert_passed_through(&path, value, serialized),
+                        None => panic!("{} was dropped", path),
+                    }
+                }
+            }
+            (Value::Array(original), Value::Array(serialized)) => {
+                assert_eq!(original.len(), serialized.len(), "{}", path);
+                for (index, (original, serialized)) in
+                    original.iter().zip(serialized.iter()).enumerate()
+                {
+                    assert_passed_through(&format!("{}[{}]", path, index), original, serialized);
+                }
+            }
+            _ => assert_eq!(original, serialized, "{}", path),
+        }
+    }
+
+    fn fixtures(prefix: &str) -> Vec<(String, Value)> {
+        let mut fixtures = Vec::new();
+
+        for directory in &["./tests/fixtures", "./tests/fixtures/contract"] {
+            for entry in std::fs::read_dir(directory).unwrap() {
+                let path = entry.unwrap().path();
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+
+                if name.starts_with(prefix) && name.ends_with(".json") {
+                    let value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+                    fixtures.push((path.display().to_string(), value));
+                }
+            }
+        }
+
+        fixtures
+    }
+
+    fn fixture(name: &str) -> Value {
+        serde_json::from_slice(&std::fs::read(format!("./tests/fixtures/{}", name)).unwrap())
+            .unwrap()
+    }
+}
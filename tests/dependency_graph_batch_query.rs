@@ -0,0 +1,265 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+
+    use rust_kata_002::dependency_graph;
+    use rust_kata_002::dependency_graph::models::{Edge, Node, QueryResult, RootError};
+    use rust_kata_002::errors::{RustKataError, RustKataResult};
+    use rust_kata_002::interfaces::crate_registry::get_crate_dependencies;
+    use rust_kata_002::interfaces::crate_registry::get_crate_dependencies::DependencyResponse;
+    use rust_kata_002::interfaces::http::{ErrorResponse, ProblemResponse};
+    use rust_kata_002::interfaces::validation::MAX_ROOTS;
+    use rust_kata_002::registry::registries::Registries;
+
+    #[actix_rt::test]
+    async fn test_dependency_graph_batch_query_bad_request_roots() {
+        let mut app = test::init_service(
+            App::new().configure(|config| dependency_graph::endpoints::config(config, "")),
+        )
+        .await;
+
+        let request = test::TestRequest::post()
+            .uri("/dependency-graph")
+            .set_json(&serde_json::json!({}))
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_client_error());
+
+        let result: ErrorResponse = test::read_body_json(response).await;
+
+        assert_eq!(result.code, "json");
+        assert!(result.description.contains("missing field `roots`"));
+    }
+
+    #[actix_rt::test]
+    async fn test_batch_query_too_many_roots() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        let roots = vec![serde_json::json!({"name": "crate-a", "version": "1.0.0"}); MAX_ROOTS + 1];
+
+        let request = test::TestRequest::post()
+            .uri("/dependency-graph")
+            .set_json(&serde_json::json!({ "roots": roots }))
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 400);
+
+        let result: ProblemResponse = test::read_body_json(response).await;
+
+        assert_eq!(result.error.code, "invalid_input");
+    }
+
+    #[actix_rt::test]
+    async fn test_batch_query_rejects_root_depth_and_format() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        for (root, field) in &[
+            (
+                serde_json::json!({"name": "crate-a", "version": "1.0.0", "depth": 1}),
+                "depth",
+            ),
+            (
+                serde_json::json!({"name": "crate-a", "version": "1.0.0", "format": "edge_list"}),
+                "format",
+            ),
+        ] {
+            let request = test::TestRequest::post()
+                .uri("/dependency-graph")
+                .set_json(&serde_json::json!({ "roots": [root] }))
+                .to_request();
+
+            let response = test::call_service(&mut app, request).await;
+
+            assert_eq!(response.status(), 400);
+
+            let result: ErrorResponse = test::read_body_json(response).await;
+
+            assert_eq!(result.code, "json");
+            assert!(result.description.contains(field), "{}", result.description);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_batch_query_failing_root() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("unknown"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| Err(RustKataError::NotFound));
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-a"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| RustKataResult::Ok(Default::default()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::post()
+            .uri("/dependency-graph")
+            .set_json(&serde_json::json!({
+                "roots": [
+                    {"name": "unknown", "version": "1.0.0"},
+                    {"name": "crate-a", "version": "1.0.0"}
+                ]
+            }))
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+
+        let result: QueryResult = test::read_body_json(response).await;
+
+        assert_eq!(
+            result,
+            QueryResult {
+                data: Some(vec![Node {
+                    name: "crate-a".to_string(),
+                    version: "1.0.0".to_string(),
+                    edges: Some(vec![]),
+                    served_by: None,
+                }]),
+                errors: Some(vec![RootError {
+                    name: "unknown".to_string(),
+                    version: "1.0.0".to_string(),
+                    code: "not_found".to_string(),
+                    description: "not found".to_string(),
+                }]),
+            }
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_batch_query_ok() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-a"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![dependency("crate-c", "^1.0.3", "normal")],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-b"), eq("2.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![dependency("crate-d", "^2.0.4", "dev")],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::post()
+            .uri("/dependency-graph")
+            .set_json(&serde_json::json!({
+                "roots": [
+                    {"name": "crate-a", "version": "1.0.0"},
+                    {"name": "crate-b", "version": "2.0.0"},
+                    {"name": "crate-a", "version": "1.0.0"}
+                ]
+            }))
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+
+        let result: QueryResult = test::read_body_json(response).await;
+
+        let crate_a = Node {
+            name: "crate-a".to_string(),
+            version: "1.0.0".to_string(),
+            edges: Some(vec![Edge {
+                relationship: "normal".to_string(),
+                error: None,
+                node: Node {
+                    name: "crate-c".to_string(),
+                    version: "^1.0.3".to_string(),
+                    edges: None,
+                    served_by: None,
+                },
+            }]),
+            served_by: None,
+        };
+
+        let crate_b = Node {
+            name: "crate-b".to_string(),
+            version: "2.0.0".to_string(),
+            edges: Some(vec![Edge {
+                relationship: "dev".to_string(),
+                error: None,
+                node: Node {
+                    name: "crate-d".to_string(),
+                    version: "^2.0.4".to_string(),
+                    edges: None,
+                    served_by: None,
+                },
+            }]),
+            served_by: None,
+        };
+
+        assert_eq!(
+            result,
+            QueryResult {
+                data: Some(vec![crate_a.clone(), crate_b, crate_a]),
+                errors: None,
+            }
+        );
+    }
+
+    fn dependency(crate_id: &str, req: &str, kind: &str) -> DependencyResponse {
+        DependencyResponse {
+            id: 1,
+            version_id: 1,
+            crate_id: crate_id.to_string(),
+            req: req.to_string(),
+            optional: false,
+            default_features: true,
+            features: None,
+            target: None,
+            kind: kind.to_string(),
+            downloads: 0,
+            registry: None,
+            extra: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+
+    use rust_kata_002::dependency_graph;
+    use rust_kata_002::dependency_graph::models::{
+        AdjacencyEdge, AdjacencyNode, AdjacencyResult, EdgeRecord,
+    };
+    use rust_kata_002::errors::RustKataResult;
+    use rust_kata_002::interfaces::crate_registry::get_crate_dependencies::DependencyResponse;
+    use rust_kata_002::interfaces::crate_registry::{get_crate, get_crate_dependencies};
+    use rust_kata_002::registry::registries::Registries;
+
+    #[actix_rt::test]
+    async fn test_query_edgelist_ok() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock()))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/dependency-graph?name=crate-a&version=1.0.0&depth=2&format=edgelist")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/x-ndjson"
+        );
+
+        let body = test::read_body(response).await;
+        let records: Vec<EdgeRecord> = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(
+            records,
+            vec![
+                record("crate-a", "1.0.0", "crate-b", "1.0.2", "normal"),
+                record("crate-b", "1.0.2", "crate-d", "^0.1", "normal"),
+                record("crate-a", "1.0.0", "crate-c", "^2.0", "dev"),
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_query_nodes_and_edges_ok() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock()))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/dependency-graph?name=crate-a&version=1.0.0&depth=2&format=nodes+edges")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+
+        let result: AdjacencyResult = test::read_body_json(response).await;
+
+        assert_eq!(
+            result,
+            AdjacencyResult {
+                edges: vec![
+                    edge(0, 1, "^1.0", "normal"),
+                    edge(1, 2, "^0.1", "normal"),
+                    edge(0, 3, "^2.0", "dev"),
+                ],
+                nodes: vec![
+                    node(0, "crate-a", "1.0.0"),
+                    node(1, "crate-b", "1.0.2"),
+                    node(2, "crate-d", "^0.1"),
+                    node(3, "crate-c", "^2.0"),
+                ],
+                error: None,
+            }
+        );
+    }
+
+    fn mock() -> MockBar {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-a"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![
+                        dependency("crate-b", "^1.0", "normal"),
+                        dependency("crate-c", "^2.0", "dev"),
+                    ],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        mock.expect_get_crate()
+            .with(eq("crate-b"))
+            .times(1)
+            .returning(|_| {
+                let mut response: get_crate::Response = serde_json::from_str(
+                    &std::fs::read_to_string("./tests/fixtures/get_crates_funny.json").unwrap(),
+                )
+                .unwrap();
+                response.versions[0].num = "1.0.2".to_string();
+                RustKataResult::Ok(response)
+            });
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-b"), eq("1.0.2"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![dependency("crate-d", "^0.1", "normal")],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        mock
+    }
+
+    fn dependency(crate_id: &str, req: &str, kind: &str) -> DependencyResponse {
+        DependencyResponse {
+            id: 1,
+            version_id: 1,
+            crate_id: crate_id.to_string(),
+            req: req.to_string(),
+            optional: false,
+            default_features: true,
+            features: None,
+            target: None,
+            kind: kind.to_string(),
+            downloads: 0,
+            registry: None,
+            extra: Default::default(),
+        }
+    }
+
+    fn record(
+        from: &str,
+        from_version: &str,
+        to: &str,
+        to_version: &str,
+        kind: &str,
+    ) -> EdgeRecord {
+        EdgeRecord {
+            from: from.to_string(),
+            from_version: from_version.to_string(),
+            to: to.to_string(),
+            to_version: to_version.to_string(),
+            kind: kind.to_string(),
+            optional: false,
+            target: None,
+            error: None,
+        }
+    }
+
+    fn node(id: usize, name: &str, version: &str) -> AdjacencyNode {
+        AdjacencyNode {
+            id,
+            name: name.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    fn edge(from: usize, to: usize, req: &str, kind: &str) -> AdjacencyEdge {
+        AdjacencyEdge {
+            from,
+            to,
+            req: req.to_string(),
+            kind: kind.to_string(),
+            optional: false,
+            target: None,
+            error: None,
+        }
+    }
+}
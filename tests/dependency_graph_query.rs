@@ -1,18 +1,19 @@
+mod common;
+
 #[cfg(test)]
 mod tests {
+    use crate::common::MockBar;
     use actix_web::{test, App};
-    use async_trait::async_trait;
     use mockall::predicate::*;
-    use mockall::*;
 
     use rust_kata_002::dependency_graph;
     use rust_kata_002::dependency_graph::models::{Edge, Node, QueryResult};
-    use rust_kata_002::errors::RustKataResult;
+    use rust_kata_002::errors::{RustKataError, RustKataResult};
     use rust_kata_002::interfaces::crate_registry::get_crate_dependencies::DependencyResponse;
-    use rust_kata_002::interfaces::crate_registry::{
-        get_crate, get_crate_dependencies, CrateRegistry,
-    };
-    use rust_kata_002::interfaces::http::ErrorResponse;
+    use rust_kata_002::interfaces::crate_registry::{get_crate, get_crate_dependencies};
+    use rust_kata_002::interfaces::http::{ErrorResponse, ProblemResponse};
+    use rust_kata_002::proxy;
+    use rust_kata_002::registry::registries::Registries;
 
     #[actix_rt::test]
     async fn test_dependency_graph_query_bad_request_name() {
@@ -22,7 +23,7 @@ mod tests {
         .await;
 
         let request = test::TestRequest::get()
-            .uri("/dependency-graph?version=version")
+            .uri("/dependency-graph?version=1.0.0")
             .to_request();
 
         let response = test::call_service(&mut app, request).await;
@@ -56,6 +57,164 @@ mod tests {
         assert_eq!(result.description, "missing field `version`");
     }
 
+    #[actix_rt::test]
+    async fn test_dependency_graph_query_invalid_input() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        for (uri, description) in &[
+            (
+                "/dependency-graph?name=serde%2F..%2F..%2Fme&version=1.0.0",
+                "invalid crate name \"serde/../../me\"",
+            ),
+            (
+                "/dependency-graph?name=..&version=1.0.0",
+                "invalid crate name \"..\"",
+            ),
+            (
+                "/dependency-graph?name=serde%3Fx%3D1&version=1.0.0",
+                "invalid crate name \"serde?x=1\"",
+            ),
+            (
+                "/dependency-graph?name=serde&version=1.0.0%2F..",
+                "invalid version \"1.0.0/..\"",
+            ),
+            (
+                "/dependency-graph?name=serde&version=%5E1.0",
+                "invalid version \"^1.0\"",
+            ),
+            (
+                "/proxy/crate?name=serde%2Fdownloads",
+                "invalid crate name \"serde/downloads\"",
+            ),
+            (
+                "/proxy/crate_dependencies?name=serde&version=latest",
+                "invalid version \"latest\"",
+            ),
+        ] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+
+            let response = test::call_service(&mut app, request).await;
+
+            assert_eq!(response.status(), 400, "{}", uri);
+
+            let result: ErrorResponse = test::read_body_json(response).await;
+
+            assert_eq!(result.code, "query");
+            assert!(
+                result.description.starts_with(description),
+                "{}: {}",
+                uri,
+                result.description
+            );
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_query_latest() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate()
+            .with(eq("crate-a"))
+            .times(1)
+            .returning(|_| {
+                let mut response: get_crate::Response = serde_json::from_str(
+                    &std::fs::read_to_string("./tests/fixtures/get_crates_funny.json").unwrap(),
+                )
+                .unwrap();
+                let version = response.versions[0].clone();
+                response.versions = vec![
+                    ("1.0.0", false),
+                    ("1.1.0", false),
+                    ("1.2.0", true),
+                    ("2.0.0-beta.1", false),
+                ]
+                .into_iter()
+                .map(|(num, yanked)| {
+                    let mut version = version.clone();
+                    version.num = num.to_string();
+                    version.yanked = yanked;
+                    version
+                })
+                .collect();
+                RustKataResult::Ok(response)
+            });
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-a"), eq("1.1.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/dependency-graph?name=crate-a&version=latest")
+            .to_request();
+
+        let result: QueryResult = test::read_response_json(&mut app, request).await;
+
+        assert_eq!(result.data.unwrap()[0].version, "1.1.0");
+    }
+
+    #[actix_rt::test]
+    async fn test_query_unknown_crate() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("unknown"), eq("1.0.0"))
+            .returning(|_, _| Err(RustKataError::NotFound));
+
+        mock.expect_get_crate()
+            .with(eq("unknown"))
+            .returning(|_| Err(RustKataError::NotFound));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        for uri in &[
+            "/dependency-graph?name=unknown&version=1.0.0",
+            "/proxy/crate?name=unknown",
+        ] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+
+            let response = test::call_service(&mut app, request).await;
+
+            assert_eq!(response.status(), 404);
+            assert_eq!(
+                response.headers().get("content-type").unwrap(),
+                "application/problem+json"
+            );
+
+            let result: ProblemResponse = test::read_body_json(response).await;
+
+            assert_eq!(result.status, 404);
+            assert_eq!(result.title, "Not Found");
+            assert_eq!(result.error.code, "not_found");
+        }
+    }
+
     #[actix_rt::test]
     async fn test_query_ok() {
         let mut mock = MockBar::new();
@@ -76,6 +235,8 @@ mod tests {
                             target: None,
                             kind: "dev".to_string(),
                             downloads: 0,
+                            registry: None,
+                            extra: Default::default(),
                         },
                         DependencyResponse {
                             id: 2,
@@ -88,20 +249,25 @@ mod tests {
                             target: None,
                             kind: "normal".to_string(),
                             downloads: 0,
+                            registry: None,
+                            extra: Default::default(),
                         },
                     ],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
                 })
             });
 
         let mut app = test::init_service(
             App::new()
                 .configure(|config| dependency_graph::endpoints::config(config, ""))
-                .data::<Box<dyn CrateRegistry>>(Box::new(mock)),
+                .data(Registries::new("crates-io", Box::new(mock))),
         )
         .await;
 
         let request = test::TestRequest::get()
-            .uri("/dependency-graph?name=name&version=version")
+            .uri("/dependency-graph?name=name&version=1.0.0")
             .to_request();
 
         let response = test::call_service(&mut app, request).await;
@@ -115,37 +281,121 @@ mod tests {
             QueryResult {
                 data: Some(vec![Node {
                     name: "name".to_string(),
-                    version: "version".to_string(),
+                    version: "1.0.0".to_string(),
                     edges: Some(vec![
                         Edge {
                             relationship: "dev".to_string(),
+                            error: None,
                             node: Node {
                                 name: "crate-a".to_string(),
                                 version: "1.0.1".to_string(),
-                                edges: None
+                                edges: None,
+                                served_by: None,
                             }
                         },
                         Edge {
                             relationship: "normal".to_string(),
+                            error: None,
                             node: Node {
                                 name: "crate-b".to_string(),
                                 version: "1.0.2".to_string(),
-                                edges: None
+                                edges: None,
+                                served_by: None,
                             }
                         }
-                    ])
-                }])
+                    ]),
+                    served_by: None,
+                }]),
+                errors: None,
             }
         );
     }
 
-    mock! {
-        pub Bar {}
+    #[actix_rt::test]
+    async fn test_query_failing_child() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("app"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![dependency("missing"), dependency("present")],
+                    ..Default::default()
+                })
+            });
+        mock.expect_get_crate_dependencies()
+            .with(eq("present"), eq("1.2.0"))
+            .times(1)
+            .returning(|_, _| RustKataResult::Ok(Default::default()));
+
+        mock.expect_get_crate()
+            .with(eq("missing"))
+            .times(1)
+            .returning(|_| Err(RustKataError::NotFound));
+        mock.expect_get_crate()
+            .with(eq("present"))
+            .times(1)
+            .returning(|_| {
+                RustKataResult::Ok(get_crate::Response {
+                    versions: vec![get_crate::VersionResponse {
+                        num: "1.2.0".to_string(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                })
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/dependency-graph?name=app&version=1.0.0&depth=2")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+
+        let result: QueryResult = test::read_body_json(response).await;
+
+        assert_eq!(
+            result.data.unwrap()[0].edges,
+            Some(vec![
+                Edge {
+                    relationship: "normal".to_string(),
+                    error: Some("not found".to_string()),
+                    node: Node {
+                        name: "missing".to_string(),
+                        version: "^1".to_string(),
+                        edges: None,
+                        served_by: None,
+                    }
+                },
+                Edge {
+                    relationship: "normal".to_string(),
+                    error: None,
+                    node: Node {
+                        name: "present".to_string(),
+                        version: "1.2.0".to_string(),
+                        edges: Some(vec![]),
+                        served_by: None,
+                    }
+                }
+            ])
+        );
+    }
 
-        #[async_trait]
-        impl CrateRegistry for Bar {
-            async fn get_crate(&self, crate_name: &str) -> RustKataResult<get_crate::Response>;
-            async fn get_crate_dependencies(&self, crate_name: &str, crate_version: &str) -> RustKataResult<get_crate_dependencies::Response>;
+    fn dependency(crate_id: &str) -> DependencyResponse {
+        DependencyResponse {
+            crate_id: crate_id.to_string(),
+            req: "^1".to_string(),
+            kind: "normal".to_string(),
+            ..Default::default()
         }
     }
 }
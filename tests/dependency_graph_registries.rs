@@ -0,0 +1,236 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+
+    use rust_kata_002::dependency_graph::models::{Edge, Node, QueryResult};
+    use rust_kata_002::errors::{RustKataError, RustKataResult};
+    use rust_kata_002::interfaces::crate_registry::get_crate_dependencies::DependencyResponse;
+    use rust_kata_002::interfaces::crate_registry::{get_crate, get_crate_dependencies};
+    use rust_kata_002::interfaces::http::ErrorResponse;
+    use rust_kata_002::registry::fallback::{FallbackRegistry, Fallthrough};
+    use rust_kata_002::registry::registries::{Registries, CRATES_IO_INDEX};
+    use rust_kata_002::{dependency_graph, index, proxy};
+
+    #[actix_rt::test]
+    async fn test_dependency_graph_unknown_registry() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        for uri in &[
+            "/dependency-graph?name=app&version=1.0.0&registry=internal",
+            "/proxy/crate?name=app&registry=internal",
+        ] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+
+            let response = test::call_service(&mut app, request).await;
+
+            assert_eq!(response.status(), 404);
+
+            let result: ErrorResponse = test::read_body_json(response).await;
+
+            assert_eq!(result.code, "not_found");
+            assert_eq!(result.description, "registry internal does not exist");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_dependency_graph_across_registries() {
+        let mut crates_io = MockBar::new();
+
+        crates_io
+            .expect_get_crate()
+            .with(eq("serde"))
+            .times(1)
+            .returning(|_| crate_response("serde", "1.0.118"));
+
+        crates_io
+            .expect_get_crate_dependencies()
+            .with(eq("serde"), eq("1.0.118"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        let mut internal = MockBar::new();
+
+        internal
+            .expect_get_crate_dependencies()
+            .with(eq("app"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![
+                        dependency("lib", None),
+                        dependency("serde", Some(CRATES_IO_INDEX)),
+                        dependency("secret", Some("https://example.com/index")),
+                    ],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        internal
+            .expect_get_crate()
+            .with(eq("lib"))
+            .times(1)
+            .returning(|_| crate_response("lib", "1.2.0"));
+
+        internal
+            .expect_get_crate_dependencies()
+            .with(eq("lib"), eq("1.2.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(
+                    Registries::new("crates-io", Box::new(crates_io))
+                        .index(CRATES_IO_INDEX, "crates-io")
+                        .registry("internal", Box::new(internal)),
+                ),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/dependency-graph?name=app&version=1.0.0&depth=2&registry=internal")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+
+        let result: QueryResult = test::read_body_json(response).await;
+
+        let edge = |name: &str, version: &str, edges: Option<Vec<Edge>>| Edge {
+            relationship: "normal".to_string(),
+            error: None,
+            node: Node {
+                name: name.to_string(),
+                version: version.to_string(),
+                edges,
+                served_by: None,
+            },
+        };
+
+        assert_eq!(
+            result,
+            QueryResult {
+                data: Some(vec![Node {
+                    name: "app".to_string(),
+                    version: "1.0.0".to_string(),
+                    edges: Some(vec![
+                        edge("lib", "1.2.0", Some(vec![])),
+                        edge("serde", "1.0.118", Some(vec![])),
+                        edge("secret", "^1", None),
+                    ]),
+                    served_by: None,
+                }]),
+                errors: None,
+            }
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_dependency_graph_records_fallback_backend() {
+        let mut mirror = MockBar::new();
+
+        mirror
+            .expect_get_crate_dependencies()
+            .with(eq("app"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| Err(RustKataError::NotFound));
+
+        let mut api = MockBar::new();
+
+        api.expect_get_crate_dependencies()
+            .with(eq("app"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new(
+                    "crates-io",
+                    Box::new(
+                        FallbackRegistry::new()
+                            .backend("local", Box::new(mirror), Fallthrough::NotFound)
+                            .backend("api", Box::new(api), Fallthrough::Any),
+                    ),
+                )),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/dependency-graph?name=app&version=1.0.0")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+
+        let result: QueryResult = test::read_body_json(response).await;
+
+        assert_eq!(result.data.unwrap()[0].served_by, Some("api".to_string()));
+    }
+
+    fn crate_response(name: &str, version: &str) -> RustKataResult<get_crate::Response> {
+        let entry = serde_json::json!({
+            "name": name,
+            "vers": version,
+            "deps": [],
+            "cksum": "",
+            "features": {},
+            "yanked": false,
+        });
+
+        index::models::crate_response(&index::models::parse(entry.to_string().as_bytes())?)
+    }
+
+    fn dependency(crate_id: &str, registry: Option<&str>) -> DependencyResponse {
+        DependencyResponse {
+            id: 1,
+            version_id: 1,
+            crate_id: crate_id.to_string(),
+            req: "^1".to_string(),
+            optional: false,
+            default_features: true,
+            features: None,
+            target: None,
+            kind: "normal".to_string(),
+            downloads: 0,
+            registry: registry.map(str::to_string),
+            extra: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+
+    use futures::channel::mpsc;
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    use rust_kata_002::dependency_graph;
+    use rust_kata_002::dependency_graph::resolver::Resolver;
+    use rust_kata_002::errors::{RustKataError, RustKataResult};
+    use rust_kata_002::interfaces::crate_registry::get_crate_dependencies;
+    use rust_kata_002::interfaces::crate_registry::get_crate_dependencies::DependencyResponse;
+    use rust_kata_002::registry::registries::Registries;
+
+    #[actix_rt::test]
+    async fn test_stream_ok() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-a"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![DependencyResponse {
+                        id: 1,
+                        version_id: 1,
+                        crate_id: "crate-b".to_string(),
+                        req: "^1.0".to_string(),
+                        optional: true,
+                        default_features: true,
+                        features: None,
+                        target: Some("cfg(unix)".to_string()),
+                        kind: "normal".to_string(),
+                        downloads: 0,
+                        registry: None,
+                        extra: Default::default(),
+                    }],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/dependency-graph/stream?name=crate-a&version=1.0.0")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/event-stream"
+        );
+
+        let body = test::read_body(response).await;
+
+        assert_eq!(
+            body,
+            concat!(
+                "event: node\n",
+                "data: {\"name\":\"crate-a\",\"version\":\"1.0.0\"}\n\n",
+                "event: edge\n",
+                "data: {\"from\":\"crate-a\",\"from_version\":\"1.0.0\",\"to\":\"crate-b\",\"to_version\":null,\"req\":\"^1.0\",\"kind\":\"normal\",\"optional\":true,\"target\":\"cfg(unix)\"}\n\n",
+                "event: done\n",
+                "data: {\"nodes\":1,\"edges\":1}\n\n",
+            )
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_resolver_waits_for_reader() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-a"), eq("1.0.0"))
+            .times(2)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: ["crate-b", "crate-c", "crate-d"]
+                        .iter()
+                        .map(|name| DependencyResponse {
+                            crate_id: name.to_string(),
+                            req: "^1.0".to_string(),
+                            kind: "normal".to_string(),
+                            ..Default::default()
+                        })
+                        .collect(),
+                    ..Default::default()
+                })
+            });
+
+        let registries = Registries::new("crates-io", Box::new(mock));
+
+        // nobody reads, so resolution stalls once the channel is full
+        let (sender, _receiver) = mpsc::channel(0);
+        let mut resolver = Resolver::new(&registries).events(sender);
+        assert!(actix_rt::time::timeout(
+            Duration::from_millis(100),
+            resolver.resolve(None, "crate-a", "1.0.0", 1),
+        )
+        .await
+        .is_err());
+
+        let (sender, receiver) = mpsc::channel(0);
+        let mut resolver = Resolver::new(&registries).events(sender);
+        let (node, events) = futures::join!(
+            async move { resolver.resolve(None, "crate-a", "1.0.0", 1).await },
+            receiver.collect::<Vec<_>>()
+        );
+
+        assert_eq!(node.unwrap().edges.unwrap().len(), 3);
+        assert_eq!(events.len(), 4);
+    }
+
+    #[actix_rt::test]
+    async fn test_stream_error() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .times(1)
+            .returning(|_, _| RustKataResult::Err(RustKataError::Transport));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| dependency_graph::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/dependency-graph/stream?name=crate-a&version=1.0.0")
+            .to_request();
+
+        let body = test::read_body(test::call_service(&mut app, request).await).await;
+
+        assert_eq!(
+            body,
+            concat!(
+                "event: error\n",
+                "data: {\"description\":\"upstream could not be reached\"}\n\n",
+                "event: done\n",
+                "data: {\"nodes\":0,\"edges\":0}\n\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,287 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+
+    use rust_kata_002::errors::RustKataResult;
+    use rust_kata_002::graphql;
+    use rust_kata_002::interfaces::crate_registry::{get_crate, get_crate_dependencies};
+    use rust_kata_002::registry::registries::Registries;
+
+    #[actix_rt::test]
+    async fn test_graphql_query_limits() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| graphql::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        // too complex: every version walks its dependencies and lists theirs in turn
+        let complex = r#"{ crate(name: "rand") { versions { dependencies(depth: 3) {
+            crate { versions { dependencies { name } } }
+        } } } }"#;
+        let deep = format!(
+            r#"{{ crate(name: "rand") {{ {} name {} }} }}"#,
+            r#"version(num: "0.8.2") { crate {"#.repeat(6),
+            "} }".repeat(6)
+        );
+
+        for (query, message) in &[
+            (complex.to_owned(), "Query is too complex."),
+            (deep, "Query is nested too deep."),
+        ] {
+            let request = test::TestRequest::post()
+                .uri("/graphql")
+                .set_json(&serde_json::json!({ "query": query }))
+                .to_request();
+
+            let response = test::call_service(&mut app, request).await;
+
+            let result: serde_json::Value = test::read_body_json(response).await;
+
+            assert_eq!(result["data"], serde_json::Value::Null, "{}", query);
+            assert_eq!(result["errors"][0]["message"], *message);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_graphql_query_ok() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate()
+            .with(eq("rand"))
+            .times(1)
+            .returning(|_| RustKataResult::Ok(fixture("get_crates_rand.json")));
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(fixture("get_crate_dependencies_rand_0.8.2.json"))
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| graphql::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let query = r#"{
+            first: crate(name: "rand") {
+                name
+                keywords { keyword }
+                version(num: "0.8.2") {
+                    num
+                    dependencies(kinds: ["normal"], features: ["default"]) { name req optional depth }
+                }
+            }
+            second: crate(name: "rand") {
+                version(num: "0.8.2") {
+                    dependencies { name }
+                }
+            }
+        }"#;
+
+        let request = test::TestRequest::post()
+            .uri("/graphql")
+            .set_json(&serde_json::json!({ "query": query }))
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+
+        let result: serde_json::Value = test::read_body_json(response).await;
+
+        assert_eq!(result["errors"], serde_json::Value::Null);
+        assert_eq!(result["data"]["first"]["name"], "rand");
+        assert_eq!(
+            result["data"]["first"]["keywords"],
+            serde_json::json!([{ "keyword": "random" }, { "keyword": "rng" }])
+        );
+        assert_eq!(result["data"]["first"]["version"]["num"], "0.8.2");
+        assert_eq!(
+            result["data"]["first"]["version"]["dependencies"],
+            serde_json::json!([
+                { "name": "rand_core", "req": "^0.6.0", "optional": false, "depth": 1 },
+                { "name": "libc", "req": "^0.2.22", "optional": true, "depth": 1 },
+                { "name": "rand_chacha", "req": "^0.3.0", "optional": true, "depth": 1 },
+                { "name": "rand_hc", "req": "^0.3.0", "optional": true, "depth": 1 }
+            ])
+        );
+        assert_eq!(
+            result["data"]["second"]["version"]["dependencies"]
+                .as_array()
+                .unwrap()
+                .len(),
+            10
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_graphql_query_does_not_walk_dev_dependencies() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate()
+            .with(eq("rand"))
+            .times(1)
+            .returning(|_| RustKataResult::Ok(fixture("get_crates_rand.json")));
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(fixture("get_crate_dependencies_rand_0.8.2.json"))
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| graphql::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let query = r#"{
+            crate(name: "rand") {
+                version(num: "0.8.2") {
+                    dependencies(depth: 2, kinds: ["dev"]) { name depth }
+                }
+            }
+        }"#;
+
+        let request = test::TestRequest::post()
+            .uri("/graphql")
+            .set_json(&serde_json::json!({ "query": query }))
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        let result: serde_json::Value = test::read_body_json(response).await;
+
+        assert_eq!(result["errors"], serde_json::Value::Null);
+        assert_eq!(
+            result["data"]["crate"]["version"]["dependencies"],
+            serde_json::json!([
+                { "name": "bincode", "depth": 1 },
+                { "name": "rand_hc", "depth": 1 },
+                { "name": "rand_pcg", "depth": 1 }
+            ])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_graphql_query_features_at_depth() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate()
+            .with(eq("root"))
+            .times(1)
+            .returning(|_| RustKataResult::Ok(crate_response("root")));
+        mock.expect_get_crate()
+            .with(eq("child"))
+            .times(1)
+            .returning(|_| RustKataResult::Ok(crate_response("child")));
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("root"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![dependency("child", false)],
+                    ..get_crate_dependencies::Response::default()
+                })
+            });
+        mock.expect_get_crate_dependencies()
+            .with(eq("child"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![dependency("optional", true), dependency("required", false)],
+                    ..get_crate_dependencies::Response::default()
+                })
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| graphql::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let query = r#"{
+            all: crate(name: "root") {
+                version(num: "1.0.0") { dependencies(depth: 2) { name depth } }
+            }
+            default: crate(name: "root") {
+                version(num: "1.0.0") {
+                    dependencies(depth: 2, features: ["default"]) { name depth }
+                }
+            }
+        }"#;
+
+        let request = test::TestRequest::post()
+            .uri("/graphql")
+            .set_json(&serde_json::json!({ "query": query }))
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        let result: serde_json::Value = test::read_body_json(response).await;
+
+        assert_eq!(result["errors"], serde_json::Value::Null);
+        assert_eq!(
+            result["data"]["all"]["version"]["dependencies"],
+            serde_json::json!([
+                { "name": "child", "depth": 1 },
+                { "name": "optional", "depth": 2 },
+                { "name": "required", "depth": 2 }
+            ])
+        );
+        assert_eq!(
+            result["data"]["default"]["version"]["dependencies"],
+            serde_json::json!([
+                { "name": "child", "depth": 1 },
+                { "name": "required", "depth": 2 }
+            ])
+        );
+    }
+
+    fn crate_response(name: &str) -> get_crate::Response {
+        get_crate::Response {
+            crate_: get_crate::CrateResponse {
+                name: name.to_owned(),
+                ..get_crate::CrateResponse::default()
+            },
+            versions: vec![get_crate::VersionResponse {
+                crate_: name.to_owned(),
+                num: "1.0.0".to_owned(),
+                features: vec![("default".to_owned(), vec![])].into_iter().collect(),
+                ..get_crate::VersionResponse::default()
+            }],
+            ..get_crate::Response::default()
+        }
+    }
+
+    fn dependency(crate_id: &str, optional: bool) -> get_crate_dependencies::DependencyResponse {
+        get_crate_dependencies::DependencyResponse {
+            crate_id: crate_id.to_owned(),
+            req: "^1".to_owned(),
+            kind: "normal".to_owned(),
+            optional,
+            default_features: true,
+            ..get_crate_dependencies::DependencyResponse::default()
+        }
+    }
+
+    fn fixture<T: for<'de> serde::Deserialize<'de>>(test_fixture_file: &str) -> T {
+        serde_json::from_str(
+            &std::fs::read_to_string(format!("./tests/fixtures/{}", test_fixture_file)).unwrap(),
+        )
+        .unwrap()
+    }
+}
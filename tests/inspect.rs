@@ -0,0 +1,130 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use mockall::predicate::*;
+
+    use rust_kata_002::inspect;
+    use rust_kata_002::inspect::models::InspectResult;
+
+    use rust_kata_002::interfaces::http::ErrorResponse;
+    use rust_kata_002::registry::registries::Registries;
+
+    #[actix_rt::test]
+    async fn test_inspect() {
+        let mut mock = MockBar::new();
+
+        mock.expect_download_crate()
+            .with(eq("sys"), eq("0.1.0"))
+            .times(1)
+            .returning(|_, _| {
+                Ok(package(&[
+                    (
+                        "Cargo.toml",
+                        "[package]\nedition = \"2021\"\nname = \"sys\"\nversion = \"0.1.0\"\nlinks = \"ssl\"\nbuild = \"build.rs\"\n",
+                    ),
+                    ("build.rs", "fn main() {}\n"),
+                    (
+                        "src/lib.rs",
+                        "extern \"C\" {\n    fn SSL_new();\n}\n\npub fn new() {\n    unsafe { SSL_new() }\n}\n",
+                    ),
+                ]))
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| inspect::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/inspect?name=sys&version=0.1.0")
+            .to_request();
+
+        let result: InspectResult = test::read_response_json(&mut app, request).await;
+
+        assert_eq!(result.manifest.edition, "2021");
+        assert_eq!(result.manifest.links.as_deref(), Some("ssl"));
+        assert_eq!(result.manifest.build.as_deref(), Some("build.rs"));
+        assert!(!result.manifest.proc_macro);
+        assert!(result.build_rs);
+        assert_eq!(result.files.len(), 3);
+        assert_eq!(result.files[2].path, "src/lib.rs");
+        assert_eq!(result.files[2].unsafe_blocks, Some(1));
+        assert_eq!(result.files[2].extern_declarations, Some(1));
+    }
+
+    #[actix_rt::test]
+    async fn test_inspect_invalid_crate() {
+        let mut mock = MockBar::new();
+
+        mock.expect_download_crate()
+            .times(1)
+            .returning(|_, _| Ok(b"not a crate".to_vec()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| inspect::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/inspect?name=sys&version=0.1.0")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 502);
+
+        let result: ErrorResponse = test::read_body_json(response).await;
+
+        assert_eq!(result.code, "deserialization");
+    }
+
+    #[actix_rt::test]
+    async fn test_inspect_invalid_input() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| inspect::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/inspect?name=sys&version=latest")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 400);
+
+        let result: ErrorResponse = test::read_body_json(response).await;
+
+        assert_eq!(result.code, "query");
+    }
+
+    fn package(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    format!("sys-0.1.0/{}", path),
+                    content.as_bytes(),
+                )
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+}
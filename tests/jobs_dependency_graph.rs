@@ -0,0 +1,310 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, web, App};
+    use async_trait::async_trait;
+    use mockall::predicate::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use rust_kata_002::dependency_graph::models::{BatchQueryParams, Edge, Node, QueryResult};
+    use rust_kata_002::errors::RustKataResult;
+    use rust_kata_002::interfaces::crate_registry::get_crate_dependencies::DependencyResponse;
+    use rust_kata_002::interfaces::crate_registry::{
+        get_crate, get_crate_dependencies, CrateRegistry,
+    };
+    use rust_kata_002::interfaces::http::ErrorResponse;
+    use rust_kata_002::jobs;
+    use rust_kata_002::jobs::models::{JobResponse, JobStatus};
+    use rust_kata_002::jobs::pool::JobPool;
+    use rust_kata_002::registry::registries::Registries;
+
+    #[actix_rt::test]
+    async fn test_get_job_not_found() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| jobs::endpoints::config(config, ""))
+                .app_data(web::Data::new(
+                    JobPool::new(1, Duration::from_secs(60)).unwrap(),
+                )),
+        )
+        .await;
+
+        let request = test::TestRequest::get().uri("/jobs/unknown").to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status().as_u16(), 404);
+
+        let result: ErrorResponse = test::read_body_json(response).await;
+
+        assert_eq!(result.code, "not_found");
+    }
+
+    #[test]
+    fn test_job_pool_rejects_invalid_config() {
+        assert!(JobPool::new(0, Duration::from_secs(60)).is_err());
+        assert!(JobPool::new(1, Duration::from_secs(u64::MAX)).is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_submit_dependency_graph_job_queue_full() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| jobs::endpoints::config(config, ""))
+                .app_data(web::Data::new(
+                    JobPool::new(1, Duration::from_secs(60))
+                        .unwrap()
+                        .max_queued(0),
+                ))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        let request = test::TestRequest::post()
+            .uri("/jobs/dependency-graph")
+            .set_json(&serde_json::json!({
+                "roots": [{"name": "crate-a", "version": "1.0.0"}]
+            }))
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status().as_u16(), 503);
+
+        let result: ErrorResponse = test::read_body_json(response).await;
+
+        assert_eq!(result.code, "queue_full");
+    }
+
+    #[actix_rt::test]
+    async fn test_submit_dependency_graph_job_ok() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-a"), eq("1.0.0"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![
+                        dependency("crate-b", "^1.0", "normal"),
+                        dependency("crate-c", "^2.0", "dev"),
+                    ],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        mock.expect_get_crate()
+            .with(eq("crate-b"))
+            .times(1)
+            .returning(|_| RustKataResult::Ok(crate_("crate-b", "1.0.2")));
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("crate-b"), eq("1.0.2"))
+            .times(1)
+            .returning(|_, _| {
+                RustKataResult::Ok(get_crate_dependencies::Response {
+                    dependencies: vec![dependency("crate-d", "^0.1", "normal")],
+                    served_by: None,
+                    upstream: Default::default(),
+                    extra: Default::default(),
+                })
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| jobs::endpoints::config(config, ""))
+                .app_data(web::Data::new(
+                    JobPool::new(1, Duration::from_secs(60)).unwrap(),
+                ))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::post()
+            .uri("/jobs/dependency-graph")
+            .set_json(&serde_json::json!({
+                "roots": [{"name": "crate-a", "version": "1.0.0"}],
+                "depth": 2
+            }))
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status().as_u16(), 202);
+
+        let job: JobResponse = test::read_body_json(response).await;
+
+        assert_eq!(job.status, JobStatus::Queued);
+
+        let mut job = job;
+        for _ in 0..100 {
+            let request = test::TestRequest::get()
+                .uri(&format!("/jobs/{}", job.id))
+                .to_request();
+
+            job = test::read_response_json(&mut app, request).await;
+
+            if job.status == JobStatus::Succeeded {
+                break;
+            }
+
+            actix_rt::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(job.status, JobStatus::Succeeded);
+        assert_eq!(job.progress.nodes_resolved, 2);
+        assert_eq!(job.progress.pending_registry_calls, 0);
+        assert_eq!(
+            job.result,
+            Some(QueryResult {
+                data: Some(vec![Node {
+                    name: "crate-a".to_string(),
+                    version: "1.0.0".to_string(),
+                    edges: Some(vec![
+                        Edge {
+                            relationship: "normal".to_string(),
+                            error: None,
+                            node: Node {
+                                name: "crate-b".to_string(),
+                                version: "1.0.2".to_string(),
+                                edges: Some(vec![Edge {
+                                    relationship: "normal".to_string(),
+                                    error: None,
+                                    node: Node {
+                                        name: "crate-d".to_string(),
+                                        version: "^0.1".to_string(),
+                                        edges: None,
+                                        served_by: None,
+                                    },
+                                }]),
+                                served_by: None,
+                            },
+                        },
+                        Edge {
+                            relationship: "dev".to_string(),
+                            error: None,
+                            node: Node {
+                                name: "crate-c".to_string(),
+                                version: "^2.0".to_string(),
+                                edges: None,
+                                served_by: None,
+                            },
+                        },
+                    ]),
+                    served_by: None,
+                }]),
+                errors: None,
+            })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_dependency_graph_job_run_timeout() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let registries = web::Data::new(Registries::new(
+            "crates-io",
+            Box::new(Hanging(calls.clone())),
+        ));
+        let pool = Arc::new(
+            JobPool::new(1, Duration::from_secs(60))
+                .unwrap()
+                .run_timeout(Duration::from_millis(50)),
+        );
+
+        let job = pool.clone().submit(registries, roots()).unwrap();
+
+        actix_rt::time::sleep(Duration::from_millis(150)).await;
+
+        let job = pool.get(&job.id).unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.error.unwrap().starts_with("timed out"));
+    }
+
+    #[actix_rt::test]
+    async fn test_dependency_graph_job_expires_while_queued() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let registries = web::Data::new(Registries::new(
+            "crates-io",
+            Box::new(Hanging(calls.clone())),
+        ));
+        let pool = Arc::new(
+            JobPool::new(1, Duration::from_millis(50))
+                .unwrap()
+                .run_timeout(Duration::from_millis(150)),
+        );
+
+        let running = pool.clone().submit(registries.clone(), roots()).unwrap();
+        let queued = pool.clone().submit(registries, roots()).unwrap();
+
+        actix_rt::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(pool.get(&running.id).unwrap().status, JobStatus::Running);
+        assert!(pool.get(&queued.id).is_none());
+
+        // the running job times out and frees its worker, which the expired job never takes
+        actix_rt::time::sleep(Duration::from_millis(150)).await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    fn roots() -> BatchQueryParams {
+        serde_json::from_value(serde_json::json!({
+            "roots": [{"name": "crate-a", "version": "1.0.0"}]
+        }))
+        .unwrap()
+    }
+
+    /// Never answers, counting the calls it was asked.
+    struct Hanging(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl CrateRegistry for Hanging {
+        async fn get_crate(&self, _: &str) -> RustKataResult<get_crate::Response> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            futures::future::pending().await
+        }
+
+        async fn get_crate_dependencies(
+            &self,
+            _: &str,
+            _: &str,
+        ) -> RustKataResult<get_crate_dependencies::Response> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            futures::future::pending().await
+        }
+    }
+
+    fn crate_(name: &str, version: &str) -> get_crate::Response {
+        let mut response: get_crate::Response = serde_json::from_str(
+            &std::fs::read_to_string("./tests/fixtures/get_crates_funny.json").unwrap(),
+        )
+        .unwrap();
+        response.crate_.name = name.to_string();
+        response.versions[0].crate_ = name.to_string();
+        response.versions[0].num = version.to_string();
+        response
+    }
+
+    fn dependency(crate_id: &str, req: &str, kind: &str) -> DependencyResponse {
+        DependencyResponse {
+            id: 1,
+            version_id: 1,
+            crate_id: crate_id.to_string(),
+            req: req.to_string(),
+            optional: false,
+            default_features: true,
+            features: None,
+            target: None,
+            kind: kind.to_string(),
+            downloads: 0,
+            registry: None,
+            extra: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+    use std::time::Duration;
+
+    use rust_kata_002::errors::RustKataError;
+    use rust_kata_002::interfaces::crate_registry::{get_crate, get_crate_dependencies, Upstream};
+    use rust_kata_002::proxy;
+    use rust_kata_002::registry::registries::Registries;
+
+    #[actix_rt::test]
+    async fn test_proxy_conditional_request() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate()
+            .with(eq("funny"))
+            .times(2)
+            .returning(|_| {
+                Ok(serde_json::from_str(
+                    &std::fs::read_to_string("./tests/fixtures/get_crates_funny.json").unwrap(),
+                )
+                .unwrap())
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate?name=funny")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers().get("cache-control").unwrap(),
+            "public, max-age=60"
+        );
+        let etag = response.headers().get("etag").unwrap().clone();
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate?name=funny")
+            .header("if-none-match", etag.clone())
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 304);
+        assert_eq!(response.headers().get("etag").unwrap(), &etag);
+        assert!(test::read_body(response).await.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_forwards_upstream_validators() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate()
+            .with(eq("funny"))
+            .times(2)
+            .returning(|_| {
+                let mut response: get_crate::Response = serde_json::from_str(
+                    &std::fs::read_to_string("./tests/fixtures/get_crates_funny.json").unwrap(),
+                )
+                .unwrap();
+                response.upstream = Upstream {
+                    etag: Some("\"a1b2\"".to_owned()),
+                    last_modified: Some("Tue, 15 Nov 1994 08:12:31 GMT".to_owned()),
+                    cache_control: Some("public, max-age=300".to_owned()),
+                };
+                Ok(response)
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate?name=funny")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers().get("etag").unwrap(), "W/\"a1b2\"");
+        assert_eq!(
+            response.headers().get("last-modified").unwrap(),
+            "Tue, 15 Nov 1994 08:12:31 GMT"
+        );
+        assert_eq!(
+            response.headers().get("cache-control").unwrap(),
+            "public, max-age=300"
+        );
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate?name=funny")
+            .header("if-modified-since", "Wed, 16 Nov 1994 08:12:31 GMT")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 304);
+        assert!(test::read_body(response).await.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_immutable_cache_control() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Ok(get_crate_dependencies::Response::default()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate_dependencies?name=rand&version=0.8.2")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers().get("cache-control").unwrap(),
+            "public, max-age=31536000, immutable"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_private_registry_cache_control() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Ok(get_crate_dependencies::Response::default()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(
+                    Registries::new("crates-io", Box::new(MockBar::new()))
+                        .registry("internal", Box::new(mock))
+                        .private("internal"),
+                ),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate_dependencies?name=rand&version=0.8.2&registry=internal")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers().get("cache-control").unwrap(),
+            "private, max-age=31536000, immutable"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_forwards_upstream_status() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate()
+            .with(eq("throttled"))
+            .times(1)
+            .returning(|_| {
+                Err(RustKataError::UpstreamStatus {
+                    status: 429,
+                    retry_after: Some(Duration::from_secs(30)),
+                })
+            });
+
+        mock.expect_get_crate()
+            .with(eq("missing"))
+            .times(1)
+            .returning(|_| Err(RustKataError::NotFound));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate?name=throttled")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 429);
+        assert_eq!(response.headers().get("retry-after").unwrap(), "30");
+        assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate?name=missing")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 404);
+        assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+    }
+}
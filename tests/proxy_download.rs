@@ -0,0 +1,103 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+
+    use rust_kata_002::errors::RustKataError;
+
+    use rust_kata_002::interfaces::http::ErrorResponse;
+    use rust_kata_002::proxy;
+    use rust_kata_002::registry::registries::Registries;
+
+    #[actix_rt::test]
+    async fn test_proxy_download() {
+        let mut mock = MockBar::new();
+
+        mock.expect_download_crate()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Ok(b"crate".to_vec()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/download/rand/0.8.2")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/gzip"
+        );
+        assert_eq!(
+            response.headers().get("content-disposition").unwrap(),
+            "attachment; filename=\"rand-0.8.2.crate\""
+        );
+        assert_eq!(
+            response.headers().get("cache-control").unwrap(),
+            "public, max-age=31536000, immutable"
+        );
+        assert_eq!(test::read_body(response).await, "crate");
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_download_integrity() {
+        let mut mock = MockBar::new();
+
+        mock.expect_download_crate()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Err(RustKataError::Integrity));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/download/rand/0.8.2")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 502);
+
+        let result: ErrorResponse = test::read_body_json(response).await;
+
+        assert_eq!(result.code, "integrity");
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_download_invalid_input() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        for uri in &[
+            "/proxy/download/rand/latest",
+            "/proxy/download/..%2Frand/0.8.2",
+            "/proxy/download/rand/0.8.2?registry=unknown",
+        ] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+
+            let response = test::call_service(&mut app, request).await;
+
+            assert!(response.status().is_client_error(), "{}", uri);
+        }
+    }
+}
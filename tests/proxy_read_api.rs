@@ -0,0 +1,215 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+    use serde_json::Value;
+
+    use rust_kata_002::errors::RustKataError;
+    use rust_kata_002::interfaces::crate_registry::{
+        get_categories, get_crate_owners, get_keyword, Page,
+    };
+    use rust_kata_002::interfaces::http::ErrorResponse;
+    use rust_kata_002::proxy;
+    use rust_kata_002::registry::registries::Registries;
+
+    #[actix_rt::test]
+    async fn test_proxy_crate_owners() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate_owners()
+            .with(eq("rand"))
+            .times(1)
+            .returning(|_| {
+                Ok(serde_json::from_value(serde_json::json!({
+                    "users": [{"id": 1, "login": "github:rust-random:maintainers", "kind": "team"}]
+                }))
+                .unwrap())
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate_owners?name=rand")
+            .to_request();
+
+        let result: get_crate_owners::Response = test::read_response_json(&mut app, request).await;
+
+        assert_eq!(result.users[0].kind, "team");
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_categories_page() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_categories()
+            .with(eq(Page {
+                page: 2,
+                per_page: 100,
+            }))
+            .times(1)
+            .returning(|_| Ok(get_categories::Response::default()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/categories?page=2&per_page=100")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_keyword() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_keyword()
+            .with(eq("rng"))
+            .times(1)
+            .returning(|_| Ok(get_keyword::Response::default()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/keyword?id=rng")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert!(response.status().is_success());
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_version_readme() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_version_readme()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| Ok("<h1>rand</h1>".to_owned()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/version_readme?name=rand&version=0.8.2")
+            .to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(test::read_body(response).await, "<h1>rand</h1>");
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_crate_versions_from_crate() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_crate()
+            .with(eq("funny"))
+            .times(1)
+            .returning(|_| {
+                Ok(serde_json::from_str(
+                    &std::fs::read_to_string("./tests/fixtures/get_crates_funny.json").unwrap(),
+                )
+                .unwrap())
+            });
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/crate_versions?name=funny")
+            .to_request();
+
+        let result: Value = test::read_response_json(&mut app, request).await;
+
+        assert_eq!(result["versions"][0]["num"], "0.0.1-alpha");
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_unsupported() {
+        let mut mock = MockBar::new();
+
+        mock.expect_get_summary()
+            .times(1)
+            .returning(|| Err(RustKataError::Unsupported));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get().uri("/proxy/summary").to_request();
+
+        let response = test::call_service(&mut app, request).await;
+
+        assert_eq!(response.status(), 501);
+
+        let result: ErrorResponse = test::read_body_json(response).await;
+
+        assert_eq!(result.code, "unsupported");
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_invalid_input() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        for (uri, code) in &[
+            ("/proxy/keywords?per_page=101", "invalid_input"),
+            (
+                "/proxy/reverse_dependencies?name=rand&page=0",
+                "invalid_input",
+            ),
+            ("/proxy/category?slug=no-std%2F..", "query"),
+            ("/proxy/version_authors?name=rand&version=latest", "query"),
+        ] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+
+            let response = test::call_service(&mut app, request).await;
+
+            assert_eq!(response.status(), 400, "{}", uri);
+
+            let result: ErrorResponse = test::read_body_json(response).await;
+
+            assert_eq!(&result.code, code, "{}", uri);
+        }
+    }
+}
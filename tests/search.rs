@@ -0,0 +1,218 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::MockBar;
+    use actix_web::{test, App};
+    use mockall::predicate::*;
+
+    use rust_kata_002::errors::RustKataError;
+    use rust_kata_002::interfaces::crate_registry::{
+        get_crate, get_crate_dependencies, search, MetaResponse, Page,
+    };
+    use rust_kata_002::interfaces::http::ErrorResponse;
+    use rust_kata_002::registry::registries::Registries;
+    use rust_kata_002::search::models::SearchResult;
+    use rust_kata_002::{proxy, search as search_endpoints};
+
+    fn candidates() -> search::Response {
+        let candidate = |name: &str, max_version: &str, downloads, recent_downloads, updated_at| {
+            get_crate::CrateResponse {
+                id: name.to_owned(),
+                name: name.to_owned(),
+                max_version: max_version.to_owned(),
+                downloads,
+                recent_downloads,
+                updated_at,
+                ..Default::default()
+            }
+        };
+
+        search::Response {
+            crates: vec![
+                candidate(
+                    "fastrand",
+                    "1.4.0",
+                    2_000_000,
+                    800_000,
+                    chrono::Utc::now().to_rfc3339(),
+                ),
+                candidate(
+                    "rand",
+                    "0.8.2",
+                    60_000_000,
+                    9_000_000,
+                    chrono::Utc::now().to_rfc3339(),
+                ),
+                candidate(
+                    "random",
+                    "0.12.2",
+                    90_000,
+                    300,
+                    "2017-06-09T08:15:02.000000+00:00".to_owned(),
+                ),
+            ],
+            meta: MetaResponse {
+                total: 3,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_search_ranks_candidates() {
+        let mut mock = MockBar::new();
+
+        mock.expect_search()
+            .with(eq(search::Query {
+                q: Some("random".to_owned()),
+                sort: Some(search::Sort::Relevance),
+                category: None,
+                keyword: None,
+                page: Page {
+                    page: 1,
+                    per_page: 50,
+                },
+            }))
+            .times(1)
+            .returning(|_| Ok(candidates()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| search_endpoints::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/search?q=random&limit=2")
+            .to_request();
+
+        let result: SearchResult = test::read_response_json(&mut app, request).await;
+
+        let names: Vec<_> = result.data.iter().map(|hit| hit.name.as_str()).collect();
+        assert_eq!(names, vec!["rand", "fastrand"]);
+        assert_eq!(result.total, 3);
+        assert!(result.data.iter().all(|hit| hit.dependencies.is_none()));
+    }
+
+    #[actix_rt::test]
+    async fn test_search_counts_dependencies() {
+        let mut mock = MockBar::new();
+
+        mock.expect_search()
+            .times(1)
+            .returning(|_| Ok(candidates()));
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("rand"), eq("0.8.2"))
+            .times(1)
+            .returning(|_, _| {
+                Ok(serde_json::from_str(
+                    &std::fs::read_to_string(
+                        "./tests/fixtures/get_crate_dependencies_rand_0.8.2.json",
+                    )
+                    .unwrap(),
+                )
+                .unwrap())
+            });
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("fastrand"), eq("1.4.0"))
+            .times(1)
+            .returning(|_, _| Err(RustKataError::Timeout));
+
+        mock.expect_get_crate_dependencies()
+            .with(eq("random"), eq("0.12.2"))
+            .times(1)
+            .returning(|_, _| Ok(get_crate_dependencies::Response::default()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| search_endpoints::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/search?q=random&dependencies=true")
+            .to_request();
+
+        let result: SearchResult = test::read_response_json(&mut app, request).await;
+
+        let dependencies: Vec<_> = result
+            .data
+            .iter()
+            .map(|hit| (hit.name.as_str(), hit.dependencies))
+            .collect();
+        assert_eq!(
+            dependencies,
+            vec![("rand", Some(7)), ("fastrand", None), ("random", Some(0))]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_search_invalid_input() {
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| search_endpoints::endpoints::config(config, ""))
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(MockBar::new()))),
+        )
+        .await;
+
+        for (uri, code) in &[
+            ("/search?q=rand&limit=0", "invalid_input"),
+            ("/search?q=rand&limit=51", "invalid_input"),
+            ("/search?category=no-std%2F..", "query"),
+            ("/proxy/search?q=rand&sort=stars", "query"),
+            ("/proxy/search?q=rand&per_page=101", "invalid_input"),
+        ] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+
+            let response = test::call_service(&mut app, request).await;
+
+            assert_eq!(response.status(), 400, "{}", uri);
+
+            let result: ErrorResponse = test::read_body_json(response).await;
+
+            assert_eq!(&result.code, code, "{}", uri);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_proxy_search() {
+        let mut mock = MockBar::new();
+
+        mock.expect_search()
+            .with(eq(search::Query {
+                q: None,
+                sort: Some(search::Sort::Downloads),
+                category: None,
+                keyword: Some("rng".to_owned()),
+                page: Page {
+                    page: 2,
+                    per_page: 20,
+                },
+            }))
+            .times(1)
+            .returning(|_| Ok(candidates()));
+
+        let mut app = test::init_service(
+            App::new()
+                .configure(|config| proxy::endpoints::config(config, ""))
+                .data(Registries::new("crates-io", Box::new(mock))),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/proxy/search?keyword=rng&sort=downloads&page=2&per_page=20")
+            .to_request();
+
+        let result: search::Response = test::read_response_json(&mut app, request).await;
+
+        assert_eq!(result.crates.len(), 3);
+        assert_eq!(result.crates[0].name, "fastrand");
+    }
+}